@@ -1,11 +1,29 @@
-use crate::fs_ops::get_current_dir;
+use crate::columns::{PanelView, ViewMode};
+use crate::compare::{CompareFlag, CompareMethod, CompareOptions, CompareResult, DirCompare};
+use crate::diskusage::{CACHE_SIZE, UsageScan, UsageTree, UsageView};
+use crate::find::{FindDialog, FindSearch};
+use crate::fuzzy::FuzzyFinder;
+use crate::loader::{DirLoad, INSTANT_LOAD, ListingCache};
+use crate::mounts::{Mount, MountSpaces, SpaceWatch, list_mounts};
+use crate::panelize::{PanelList, PanelizeRun};
+use crate::preview::Preview;
+use crate::quickview::QuickView;
+use crate::settings::{SPLIT_MAX, SPLIT_MIN, Settings};
+use crate::sync::{SyncDialog, SyncPlan};
+use crate::fs_ops::{ItemDetails, PanelFilter, get_current_dir, parent_item, load_list_rows, sort_rows};
+use crate::utils::{display_name, format_size, fuzzy_match, wildcard_positions};
 use crate::viewer::ViewerState;
+use crate::watch::{PanelWatch, WatchMode};
+use ratatui::layout::Rect;
 use ratatui::style::Style;
 use ratatui::text::Span;
 use ratatui::widgets::TableState;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
-use std::time::Instant;
+use std::ffi::OsStr;
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
 
 /// Reusable single-line text input with cursor.
 pub struct TextInput {
@@ -110,19 +128,48 @@ pub struct AppState {
     pub rename_input: TextInput,
     pub create_input: TextInput,
     pub is_f8_displayed: bool,
-    pub delete_items: Vec<(String, bool)>,
+    pub delete_items: Vec<(PathBuf, bool)>,
     pub search_input: String,
+    pub search_mode: SearchMode,
+    pub is_search_filter: bool,
+    pub search_stash_left: Option<Vec<Item>>,
+    pub search_stash_right: Option<Vec<Item>>,
+    pub tree_left: bool,
+    pub tree_right: bool,
+    pub expanded_left: HashSet<PathBuf>,
+    pub expanded_right: HashSet<PathBuf>,
+    pub is_miller: bool,
+    pub view_left: PanelView,
+    pub view_right: PanelView,
+    pub is_columns_displayed: bool,
+    pub columns_cursor: usize,
+    pub miller_parent: Option<Preview>,
+    pub miller_preview: Option<Preview>,
+    pub is_quick_view: bool,
+    pub quick_view: QuickView,
     pub cached_clock: String,
     pub cached_separator_height: u16,
     pub cached_separator: String,
+    pub split_percent: u16,
+    pub is_split_horizontal: bool,
+    pub is_zoomed: bool,
+    pub is_dragging_split: bool,
+    pub panel_layout: PanelLayout,
     pub is_f3_displayed: bool,
     pub viewer_state: Option<ViewerState>,
     pub viewer_viewport_height: usize,
+    /// Go to offset (hex) or line (text) prompt in the viewer status bar.
+    pub is_viewer_goto_displayed: bool,
+    pub viewer_goto_input: TextInput,
     pub is_f4_displayed: bool,
     pub editor_state: Option<EditorState>,
     pub editor_viewport_height: usize,
     pub is_f5_displayed: bool,
     pub copy_items: Vec<(PathBuf, PathBuf, bool)>,
+    /// How many of `copy_items` already exist at the destination and will be overwritten.
+    pub copy_overwrites: usize,
+    /// Copying entries marked by the last compare, the only copies allowed to replace existing destinations.
+    pub is_compare_copy: bool,
     pub is_f6_displayed: bool,
     pub move_items: Vec<(PathBuf, PathBuf, bool)>,
     pub selected_left: HashSet<usize>,
@@ -131,6 +178,126 @@ pub struct AppState {
     pub last_click_time: Option<Instant>,
     pub last_click_pos: (u16, u16),
     pub is_editor_save_prompt: bool,
+    pub filter_left: PanelFilter,
+    pub filter_right: PanelFilter,
+    pub total_left: usize,
+    pub total_right: usize,
+    /// Why the panel's directory could only be partly read, or not at all.
+    pub listing_error_left: Option<String>,
+    pub listing_error_right: Option<String>,
+    /// Rows of the listing (the rows behind the search filter, if any) that could not be read, counted when it changes.
+    pub unreadable_left: usize,
+    pub unreadable_right: usize,
+    pub is_filter_displayed: bool,
+    pub filter_input: TextInput,
+    pub is_find_displayed: bool,
+    pub find_dialog: FindDialog,
+    pub find_search: Option<FindSearch>,
+    pub is_fuzzy_displayed: bool,
+    pub fuzzy_finder: Option<FuzzyFinder>,
+    pub list_left: Option<PanelList>,
+    pub list_right: Option<PanelList>,
+    pub is_panelize_displayed: bool,
+    pub panelize_input: TextInput,
+    /// The command collecting paths for the panelize popup, which stays open until it is done.
+    pub panelize_run: Option<PanelizeRun>,
+    pub is_compare_displayed: bool,
+    pub compare_options: CompareOptions,
+    /// 0 = method, 1 = subdirectories.
+    pub compare_cursor: usize,
+    pub dir_compare: Option<DirCompare>,
+    /// Outcome of a comparison that found nothing to mark.
+    pub compare_message: String,
+    pub compare_left: HashMap<PathBuf, CompareFlag>,
+    pub compare_right: HashMap<PathBuf, CompareFlag>,
+    pub is_sync_displayed: bool,
+    pub sync_dialog: SyncDialog,
+    pub sync_plan: Option<SyncPlan>,
+    /// Target file being typed while exporting the plan.
+    pub sync_export: Option<TextInput>,
+    pub is_du_displayed: bool,
+    pub du_scan: Option<UsageScan>,
+    pub du_view: Option<UsageView>,
+    /// Finished scans, oldest first, reused when the view is reopened inside them.
+    pub du_cache: Vec<UsageTree>,
+    pub space_left: SpaceWatch,
+    pub space_right: SpaceWatch,
+    pub is_mounts_displayed: bool,
+    pub mounts: Vec<Mount>,
+    pub mount_spaces: Option<MountSpaces>,
+    pub mounts_selected: usize,
+    /// Panel the mounts popup switches.
+    pub mounts_for_left: bool,
+    pub watch_mode: WatchMode,
+    /// Copies recreate device nodes (`copy_devices=true`) instead of refusing them.
+    pub copy_devices: bool,
+    pub watch_left: PanelWatch,
+    pub watch_right: PanelWatch,
+    /// Listings still streaming in.
+    pub load_left: Option<DirLoad>,
+    pub load_right: Option<DirLoad>,
+    pub listing_cache: ListingCache,
+}
+
+/// How the type-ahead query is matched against names (Ctrl+S cycles).
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    Prefix,
+    Substring,
+    Fuzzy,
+    Glob,
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Prefix => SearchMode::Substring,
+            SearchMode::Substring => SearchMode::Fuzzy,
+            SearchMode::Fuzzy => SearchMode::Glob,
+            SearchMode::Glob => SearchMode::Prefix,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Prefix => "prefix",
+            SearchMode::Substring => "substring",
+            SearchMode::Fuzzy => "fuzzy",
+            SearchMode::Glob => "glob",
+        }
+    }
+
+    /// Case-insensitive; returns a score and the matched char positions in `name`.
+    /// Globs get an implicit trailing `*` so partial input keeps matching while typing.
+    pub fn match_name(self, query: &str, name: &str) -> Option<(i32, Vec<usize>)> {
+        let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+        let query: Vec<char> = query.chars().map(fold).collect();
+        let name_chars: Vec<char> = name.chars().map(fold).collect();
+        let find_at = |start: usize| name_chars[start..].starts_with(&query).then(|| (0, (start..start + query.len()).collect()));
+        match self {
+            SearchMode::Prefix => find_at(0),
+            SearchMode::Substring => (0..=name_chars.len().saturating_sub(query.len())).find_map(find_at),
+            SearchMode::Fuzzy => fuzzy_match(&query.iter().collect::<String>(), name),
+            SearchMode::Glob => wildcard_positions(&format!("{}*", query.iter().collect::<String>()), name).map(|positions| (0, positions)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FuzzyAction {
+    GoTo,
+    View,
+    Edit,
+}
+
+/// Screen areas of the dual-pane view from the last frame, used for mouse hit-testing.
+/// A hidden panel (zoom) has an empty rect.
+#[derive(Clone, Copy, Default)]
+pub struct PanelLayout {
+    pub area: Rect,
+    pub left: Rect,
+    pub separator: Rect,
+    pub right: Rect,
 }
 
 #[derive(Clone)]
@@ -148,19 +315,51 @@ pub struct EditorState {
 
 #[derive(Debug, Clone)]
 pub struct Item {
+    /// Absolute path of the entry; for ".." the directory it leads to. File operations use it
+    /// (or `os_name`), never the names below, which are for display and may carry escapes.
+    pub path: PathBuf,
     pub name_full: String,
     pub name: String,
     pub extension: String,
     pub is_dir: bool,
-    pub size: String,
     pub size_bytes: u64,
-    pub modified: String,
+    pub modified: Option<SystemTime>,
+    /// Nesting level in tree view, 0 for top-level rows.
+    pub depth: usize,
+    pub details: ItemDetails,
+    /// Why the entry's metadata (or, for an expanded tree directory, its contents) could not be read.
+    pub error: Option<String>,
+}
+
+impl Item {
+    /// Last component of `name_full`, which is a relative path for nested tree rows.
+    pub fn file_name(&self) -> &str {
+        self.name_full.rsplit(std::path::is_separator).next().unwrap_or(&self.name_full)
+    }
+
+    /// The entry's name as stored on disk.
+    pub fn os_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    /// Size column text; `dir_size` is the calculated size of a directory row, if known.
+    pub fn size_text(&self, dir_size: Option<u64>) -> String {
+        match dir_size {
+            Some(size) => format_size(size),
+            None if self.error.is_some() => "<ERR>".to_string(),
+            None if self.name == ".." => String::new(),
+            None if self.is_dir => "<DIR>".to_string(),
+            // A link's own length is that of its target path, special files have none
+            None => self.details.kind.marker().map_or_else(|| format_size(self.size_bytes), str::to_string),
+        }
+    }
 }
 
 impl AppState {
     pub fn new() -> Self {
         let mut state_left = TableState::default();
         state_left.select(Some(1));
+        let settings = Settings::load();
         let mut state_right = TableState::default();
         state_right.select(Some(1));
 
@@ -188,17 +387,43 @@ impl AppState {
             is_f8_displayed: false,
             delete_items: Vec::new(),
             search_input: String::new(),
+            search_mode: SearchMode::Prefix,
+            is_search_filter: false,
+            search_stash_left: None,
+            search_stash_right: None,
+            tree_left: false,
+            tree_right: false,
+            expanded_left: HashSet::new(),
+            expanded_right: HashSet::new(),
+            is_miller: false,
+            view_left: PanelView::new(),
+            view_right: PanelView::new(),
+            is_columns_displayed: false,
+            columns_cursor: 0,
+            miller_parent: None,
+            miller_preview: None,
+            is_quick_view: false,
+            quick_view: QuickView::new(),
             cached_clock: String::new(),
             cached_separator_height: 0,
             cached_separator: String::new(),
+            split_percent: settings.split_percent,
+            is_split_horizontal: settings.is_split_horizontal,
+            is_zoomed: false,
+            is_dragging_split: false,
+            panel_layout: PanelLayout::default(),
             is_f3_displayed: false,
             viewer_state: None,
             viewer_viewport_height: 0,
+            is_viewer_goto_displayed: false,
+            viewer_goto_input: TextInput::new(),
             is_f4_displayed: false,
             editor_state: None,
             editor_viewport_height: 0,
             is_f5_displayed: false,
             copy_items: Vec::new(),
+            copy_overwrites: 0,
+            is_compare_copy: false,
             is_f6_displayed: false,
             move_items: Vec::new(),
             selected_left: HashSet::new(),
@@ -207,9 +432,569 @@ impl AppState {
             last_click_time: None,
             last_click_pos: (0, 0),
             is_editor_save_prompt: false,
+            filter_left: PanelFilter::new(),
+            filter_right: PanelFilter::new(),
+            total_left: 0,
+            total_right: 0,
+            listing_error_left: None,
+            listing_error_right: None,
+            unreadable_left: 0,
+            unreadable_right: 0,
+            is_filter_displayed: false,
+            filter_input: TextInput::new(),
+            is_find_displayed: false,
+            find_dialog: FindDialog::new(),
+            find_search: None,
+            is_fuzzy_displayed: false,
+            fuzzy_finder: None,
+            list_left: None,
+            list_right: None,
+            is_panelize_displayed: false,
+            panelize_input: TextInput::new(),
+            panelize_run: None,
+            is_compare_displayed: false,
+            compare_options: CompareOptions { method: CompareMethod::Quick, recursive: false },
+            compare_cursor: 0,
+            dir_compare: None,
+            compare_message: String::new(),
+            compare_left: HashMap::new(),
+            compare_right: HashMap::new(),
+            is_sync_displayed: false,
+            sync_dialog: SyncDialog::new(),
+            sync_plan: None,
+            sync_export: None,
+            is_du_displayed: false,
+            du_scan: None,
+            du_view: None,
+            du_cache: Vec::new(),
+            space_left: SpaceWatch::new(),
+            space_right: SpaceWatch::new(),
+            is_mounts_displayed: false,
+            mounts: Vec::new(),
+            mount_spaces: None,
+            mounts_selected: 0,
+            mounts_for_left: true,
+            watch_mode: settings.watch_mode,
+            copy_devices: settings.copy_devices,
+            watch_left: PanelWatch::new(settings.watch_mode),
+            watch_right: PanelWatch::new(settings.watch_mode),
+            load_left: None,
+            load_right: None,
+            listing_cache: ListingCache::new(),
+        }
+    }
+
+    /// Collects results from background workers; called once per main loop tick.
+    pub fn poll_background(&mut self) {
+        if let Some(search) = &mut self.find_search {
+            search.poll();
+        }
+        if let Some(finder) = &mut self.fuzzy_finder {
+            finder.poll();
+        }
+        if self.is_quick_view {
+            self.quick_view.poll();
+        }
+        for preview in [&mut self.miller_parent, &mut self.miller_preview].into_iter().flatten() {
+            preview.poll();
+        }
+        if let Some(plan) = &mut self.sync_plan {
+            plan.poll();
+            if plan.run.as_ref().is_some_and(|run| run.done) {
+                self.finish_sync();
+            }
+        }
+        self.poll_panelize();
+        self.poll_mount_spaces();
+        if let Some(tree) = self.du_scan.as_mut().and_then(|scan| scan.poll()) {
+            self.du_scan = None;
+            // Panels show the totals of the scanned directory's children without another walk
+            for row in tree.rows(&tree.root, true).into_iter().filter(|row| row.is_dir) {
+                self.dir_sizes.insert(row.path, row.usage.apparent);
+            }
+            let root = tree.root.clone();
+            self.du_view = Some(UsageView::new(tree, root));
+        }
+        if let Some(viewer) = &mut self.viewer_state && let Some(error) = viewer.poll() {
+            let message = format!("Cannot read {}: {}", display_name(viewer.file_path.as_os_str()), error);
+            self.display_error(message);
+        }
+        if let Some(compare) = &mut self.dir_compare {
+            compare.poll();
+            if let Some(result) = compare.result.take() {
+                self.dir_compare = None;
+                self.apply_compare(result);
+            }
+        }
+        for is_left in [true, false] {
+            self.poll_dir_load(is_left);
+            let dirs = self.watched_dirs(is_left);
+            let watch = if is_left { &mut self.watch_left } else { &mut self.watch_right };
+            watch.watch(dirs);
+            if watch.poll() {
+                self.refresh_watched_panel(is_left);
+            }
+        }
+    }
+
+    /// The panel directory plus, in tree view, every expanded directory below it; nothing for panelized lists.
+    fn watched_dirs(&self, is_left: bool) -> Vec<PathBuf> {
+        let (dir, list, is_tree, expanded) = if is_left {
+            (&self.dir_left, &self.list_left, self.tree_left, &self.expanded_left)
+        } else {
+            (&self.dir_right, &self.list_right, self.tree_right, &self.expanded_right)
+        };
+        if list.is_some() {
+            return Vec::new();
+        }
+        let mut nested: Vec<PathBuf> = if is_tree { expanded.iter().filter(|path| path.starts_with(dir) && *path != dir).cloned().collect() } else { Vec::new() };
+        nested.sort();
+        std::iter::once(dir.clone()).chain(nested).collect()
+    }
+
+    /// Reloads a panel changed from outside, keeping the cursor and selections on the same names.
+    fn refresh_watched_panel(&mut self, is_left: bool) {
+        let (dir, is_tree, is_loading) = if is_left { (self.dir_left.clone(), self.tree_left, self.load_left.is_some()) } else { (self.dir_right.clone(), self.tree_right, self.load_right.is_some()) };
+        // The listing being read already includes the change, or will
+        if is_loading {
+            return;
+        }
+        // A directory removed from under the panel gives way to its closest surviving ancestor
+        let Some(existing) = dir.ancestors().find(|path| path.is_dir()).map(Path::to_path_buf) else { return };
+        if existing != dir {
+            self.open_dir(is_left, existing, None);
+        } else if is_tree {
+            self.refresh_listing(is_left, None);
+        } else {
+            // Unreadable now (e.g. permissions changed): the refresh keeps showing the last listing
+            let filter = if is_left { self.filter_left.clone() } else { self.filter_right.clone() };
+            let load = DirLoad::refresh(dir, filter);
+            if is_left {
+                self.load_left = Some(load);
+            } else {
+                self.load_right = Some(load);
+            }
+        }
+    }
+
+    /// Switches a panel to `dir`, with the cursor on the row for the `select` path if given. Listings taking longer than
+    /// `INSTANT_LOAD` keep streaming in from a background thread while the panel stays usable.
+    pub fn open_dir(&mut self, is_left: bool, dir: PathBuf, select: Option<PathBuf>) {
+        let (is_tree, filter, expanded) = if is_left { (self.tree_left, &self.filter_left, &self.expanded_left) } else { (self.tree_right, &self.filter_right, &self.expanded_right) };
+        let filter = filter.clone();
+        // Trees are never cached, they also hold every expanded directory
+        let cached = if is_tree { None } else { self.listing_cache.take(&dir, &filter) };
+        let result = if let Some((items, total)) = cached {
+            // Incomplete listings are never cached
+            self.reset_listing(is_left, total, None);
+            // Shown while the directory is read again, as anything may have changed since
+            Ok((items, Some(DirLoad::refresh(dir.clone(), filter))))
+        } else {
+            let mut load = if is_tree { DirLoad::start_tree(dir.clone(), filter.clone(), expanded.clone(), false) } else { DirLoad::start(dir.clone(), filter.clone()) };
+            let mut items = load.wait(INSTANT_LOAD);
+            match load.open_error.take() {
+                Some(e) => Err(e),
+                None => {
+                    self.reset_listing(is_left, load.entries, load.error.clone());
+                    if load.done {
+                        if !is_tree {
+                            sort_rows(&mut items);
+                            if load.error.is_none() {
+                                self.listing_cache.insert(dir.clone(), filter, items.clone(), load.entries);
+                            }
+                        }
+                        Ok((items, None))
+                    } else {
+                        load.select = select.clone();
+                        Ok((items, Some(load)))
+                    }
+                }
+            }
+        };
+        let (items, load) = match result {
+            Ok(loaded) => loaded,
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                // Entered anyway, empty, with the reason in the path bar
+                self.reset_listing(is_left, 0, Some(e.to_string()));
+                (dir.parent().map(|parent| parent_item(parent.to_path_buf())).into_iter().collect(), None)
+            }
+            Err(e) => {
+                // The panel stays where it is
+                self.display_error(format!("Cannot open {}: {}", display_name(dir.as_os_str()), e));
+                return;
+            }
+        };
+
+        let index = select.and_then(|path| items.iter().position(|item| item.path == path)).unwrap_or(0);
+        // Compare marks outlive reloads of the same directory
+        if is_left {
+            if self.dir_left != dir {
+                self.compare_left.clear();
+            }
+            self.dir_left = dir;
+            self.children_left = items;
+            self.state_left.select(Some(index));
+            self.selected_left.clear();
+            self.load_left = load;
+        } else {
+            if self.dir_right != dir {
+                self.compare_right.clear();
+            }
+            self.dir_right = dir;
+            self.children_right = items;
+            self.state_right.select(Some(index));
+            self.selected_right.clear();
+            self.load_right = load;
+        }
+        self.count_unreadable(is_left);
+        self.search_clear();
+    }
+
+    /// Moves newly read rows into the panel (behind the search filter, if any), then sorts the listing once it is complete.
+    fn poll_dir_load(&mut self, is_left: bool) {
+        let (load, children, stash, state) = if is_left {
+            (&mut self.load_left, &mut self.children_left, &mut self.search_stash_left, &self.state_left)
+        } else {
+            (&mut self.load_right, &mut self.children_right, &mut self.search_stash_right, &self.state_right)
+        };
+        let Some(load) = load else { return };
+        let rows = load.poll();
+        if load.is_refresh {
+            load.rows.extend(rows);
+        } else {
+            // Appended at the end, so the rows under the cursor and selections keep their indices
+            *if is_left { &mut self.unreadable_left } else { &mut self.unreadable_right } += rows.iter().filter(|item| item.error.is_some()).count();
+            stash.as_mut().unwrap_or(children).extend(rows);
+            if state.selected().is_some_and(|index| index > 0) {
+                // Moved by the user, who no longer waits for the row to arrive
+                load.select = None;
+            }
+        }
+        // A refresh moves the cursor only once its rows replace the old ones
+        if !load.is_refresh && let Some(index) = load.select.as_ref().and_then(|path| children.iter().position(|item| &item.path == path)) {
+            load.select = None;
+            if is_left { self.state_left.select(Some(index)) } else { self.state_right.select(Some(index)) }
+            return;
+        }
+        if !load.done {
+            return;
+        }
+
+        let Some(mut load) = (if is_left { self.load_left.take() } else { self.load_right.take() }) else { return };
+        if let Some(e) = load.open_error.take() {
+            // A refresh keeps showing the last listing; a directory entered while it was being opened stays empty
+            if !load.is_refresh {
+                self.reset_listing(is_left, 0, Some(e.to_string()));
+                let dir = if is_left { &self.dir_left } else { &self.dir_right };
+                let rows = dir.parent().map(|parent| parent_item(parent.to_path_buf())).into_iter().collect();
+                self.replace_children(is_left, rows);
+            }
+            return;
+        }
+        let stash = if is_left { self.search_stash_left.take() } else { self.search_stash_right.take() };
+        let rows = if load.is_refresh { Some(std::mem::take(&mut load.rows)) } else { stash };
+        let is_complete = load.error.is_none();
+        self.reset_listing(is_left, load.entries, load.error.take());
+        match rows {
+            Some(mut items) => {
+                if !load.is_tree {
+                    sort_rows(&mut items);
+                }
+                self.replace_children(is_left, items);
+            }
+            // Tree rows arrive in tree order
+            None if load.is_tree => {}
+            // The panel holds the whole listing, sorted where it is
+            None => self.update_children(is_left, |children| sort_rows(children)),
+        }
+        if let Some(path) = load.select.take() {
+            let (children, state) = if is_left { (&self.children_left, &mut self.state_left) } else { (&self.children_right, &mut self.state_right) };
+            if let Some(index) = children.iter().position(|item| item.path == path) {
+                state.select(Some(index));
+            }
+        }
+        if is_complete && !load.is_tree {
+            self.cache_listing(is_left);
+        }
+        if self.is_search_filter && is_left == self.is_left_active && !self.search_input.is_empty() {
+            self.apply_search_filter();
+        }
+    }
+
+    /// Remembers the panel's complete listing (the rows behind the search filter, if any) for coming back to it.
+    fn cache_listing(&mut self, is_left: bool) {
+        let (dir, filter, rows, total) = if is_left {
+            (&self.dir_left, &self.filter_left, self.search_stash_left.as_ref().unwrap_or(&self.children_left), self.total_left)
+        } else {
+            (&self.dir_right, &self.filter_right, self.search_stash_right.as_ref().unwrap_or(&self.children_right), self.total_right)
+        };
+        self.listing_cache.insert(dir.clone(), filter.clone(), rows.clone(), total);
+    }
+
+    /// A fresh listing supersedes rows stashed by the search filter, any panelized list
+    /// and a listing still being read; `total` is its unfiltered entry count, `error` what cut it short.
+    fn reset_listing(&mut self, is_left: bool, total: usize, error: Option<String>) {
+        if is_left {
+            self.total_left = total;
+            self.listing_error_left = error;
+            self.search_stash_left = None;
+            self.list_left = None;
+            self.load_left = None;
+        } else {
+            self.total_right = total;
+            self.listing_error_right = error;
+            self.search_stash_right = None;
+            self.list_right = None;
+            self.load_right = None;
+        }
+    }
+
+    /// Rows of the panelized list, dropping paths that no longer exist.
+    fn load_list_panel_rows(&mut self, is_left: bool) -> Vec<Item> {
+        let (list, dir, filter) = if is_left {
+            (&mut self.list_left, &self.dir_left, &self.filter_left)
+        } else {
+            (&mut self.list_right, &self.dir_right, &self.filter_right)
+        };
+        let Some(list) = list else { return Vec::new() };
+        list.paths.retain(|path| path.symlink_metadata().is_ok());
+        let (items, total) = load_list_rows(dir, &list.paths, filter);
+        if is_left {
+            self.total_left = total;
+            self.listing_error_left = None;
+            self.search_stash_left = None;
+        } else {
+            self.total_right = total;
+            self.listing_error_right = None;
+            self.search_stash_right = None;
+        }
+        items
+    }
+
+    /// Re-reads the panel's current directory (or list), keeping the cursor and selections on the same names.
+    pub fn reload_panel(&mut self, is_left: bool) {
+        self.reload_panel_selecting(is_left, None);
+    }
+
+    /// Like `reload_panel`, then puts the cursor on `select` once its row is in.
+    pub fn reload_panel_selecting(&mut self, is_left: bool, select: Option<PathBuf>) {
+        let is_list = if is_left { self.list_left.is_some() } else { self.list_right.is_some() };
+        if !is_list {
+            self.refresh_listing(is_left, select);
+            return;
+        }
+        let items = self.load_list_panel_rows(is_left);
+        self.replace_children(is_left, items);
+        let (children, state) = if is_left { (&self.children_left, &mut self.state_left) } else { (&self.children_right, &mut self.state_right) };
+        if let Some(index) = select.and_then(|path| children.iter().position(|item| item.path == path)) {
+            state.select(Some(index));
+        }
+        if self.is_search_filter && is_left == self.is_left_active && !self.search_input.is_empty() {
+            self.apply_search_filter();
+        }
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        let is_left = self.is_left_active;
+        let filter = if is_left { &mut self.filter_left } else { &mut self.filter_right };
+        filter.show_hidden = !filter.show_hidden;
+        self.clear_active_selections();
+        self.reload_panel(is_left);
+    }
+
+    /// Switches the active panel between flat listing and tree view.
+    pub fn toggle_tree_view(&mut self) {
+        let is_left = self.is_left_active;
+        let is_tree = if is_left { &mut self.tree_left } else { &mut self.tree_right };
+        *is_tree = !*is_tree;
+        self.search_clear();
+        self.clear_active_selections();
+        self.reload_panel(is_left);
+    }
+
+    /// Panelized lists are always flat.
+    pub fn is_tree_active(&self) -> bool {
+        !self.is_list_active() && if self.is_left_active { self.tree_left } else { self.tree_right }
+    }
+
+    /// Right in tree view: expands a collapsed directory, or steps into an expanded one.
+    pub fn tree_expand(&mut self) {
+        let Some((index, item, path)) = self.tree_cursor() else { return };
+        if !item.is_dir || item.name == ".." {
+            return;
+        }
+        let expanded = if self.is_left_active { &mut self.expanded_left } else { &mut self.expanded_right };
+        if expanded.insert(path) {
+            self.reload_tree();
+        } else {
+            let children = if self.is_left_active { &self.children_left } else { &self.children_right };
+            if children.get(index + 1).is_some_and(|next| next.depth > item.depth) {
+                self.active_panel_mut().1.select(Some(index + 1));
+            }
+        }
+    }
+
+    /// Left in tree view: collapses an expanded directory, or jumps to the parent node.
+    pub fn tree_collapse(&mut self) {
+        let Some((index, item, path)) = self.tree_cursor() else { return };
+        let expanded = if self.is_left_active { &mut self.expanded_left } else { &mut self.expanded_right };
+        // Nested expanded directories are forgotten along with their parent
+        if expanded.remove(&path) {
+            expanded.retain(|dir| !dir.starts_with(&path));
+            self.reload_tree();
+        } else if item.depth > 0 {
+            let (children, state) = self.active_panel_mut();
+            if let Some(parent) = children[..index].iter().rposition(|row| row.depth < item.depth) {
+                state.select(Some(parent));
+            }
+        }
+    }
+
+    /// The active panel's cursor row with its absolute path.
+    fn tree_cursor(&self) -> Option<(usize, Item, PathBuf)> {
+        let (children, state) = if self.is_left_active { (&self.children_left, &self.state_left) } else { (&self.children_right, &self.state_right) };
+        let index = state.selected()?;
+        let item = children.get(index)?.clone();
+        let path = item.path.clone();
+        Some((index, item, path))
+    }
+
+    /// Reloads the active tree, keeping selections by name (rows shift as nodes open and close).
+    fn reload_tree(&mut self) {
+        self.refresh_listing(self.is_left_active, None);
+    }
+
+    /// Re-reads a panel's directory or tree in the background. Its rows stay until the new listing is complete,
+    /// which takes effect at once when it is ready within `INSTANT_LOAD`; then the cursor goes to `select`, if given.
+    fn refresh_listing(&mut self, is_left: bool, select: Option<PathBuf>) {
+        let (dir, filter, is_tree, expanded) = if is_left { (&self.dir_left, &self.filter_left, self.tree_left, &self.expanded_left) } else { (&self.dir_right, &self.filter_right, self.tree_right, &self.expanded_right) };
+        let mut load = if is_tree { DirLoad::start_tree(dir.clone(), filter.clone(), expanded.clone(), true) } else { DirLoad::refresh(dir.clone(), filter.clone()) };
+        let rows = load.wait(INSTANT_LOAD);
+        load.rows.extend(rows);
+        load.select = select;
+        if is_left {
+            self.load_left = Some(load);
+        } else {
+            self.load_right = Some(load);
+        }
+        self.poll_dir_load(is_left);
+    }
+
+    pub fn active_view_mut(&mut self) -> &mut PanelView {
+        if self.is_left_active { &mut self.view_left } else { &mut self.view_right }
+    }
+
+    pub fn is_brief_active(&self) -> bool {
+        let view = if self.is_left_active { &self.view_left } else { &self.view_right };
+        view.mode == ViewMode::Brief
+    }
+
+    pub fn cycle_view_mode(&mut self) {
+        let view = self.active_view_mut();
+        view.mode = view.mode.next();
+    }
+
+    /// Column editor for the active panel; switches it to full mode so changes are visible.
+    pub fn open_columns(&mut self) {
+        self.active_view_mut().mode = ViewMode::Full;
+        self.columns_cursor = 0;
+        self.is_columns_displayed = true;
+    }
+
+    pub fn toggle_miller(&mut self) {
+        self.is_miller = !self.is_miller;
+        self.miller_parent = None;
+        self.miller_preview = None;
+        // Miller columns bring their own preview
+        self.is_quick_view = false;
+    }
+
+    /// Moves the split by `delta` percent (Ctrl+Left/Right).
+    pub fn resize_split(&mut self, delta: i16) {
+        self.split_percent = self.split_percent.saturating_add_signed(delta).clamp(SPLIT_MIN, SPLIT_MAX);
+        self.is_zoomed = false;
+        self.save_settings();
+    }
+
+    /// Follows a separator drag to the given screen cell.
+    pub fn drag_split(&mut self, column: u16, row: u16) {
+        let area = self.panel_layout.area;
+        let (offset, length) = if self.is_split_horizontal { (row.saturating_sub(area.y), area.height) } else { (column.saturating_sub(area.x), area.width) };
+        let length = length.saturating_sub(1).max(1) as u32;
+        let percent = (offset as u32 * 100 + length / 2) / length;
+        self.split_percent = (percent as u16).clamp(SPLIT_MIN, SPLIT_MAX);
+    }
+
+    pub fn toggle_zoom(&mut self) {
+        self.is_zoomed = !self.is_zoomed;
+    }
+
+    pub fn toggle_split_orientation(&mut self) {
+        self.is_split_horizontal = !self.is_split_horizontal;
+        self.save_settings();
+    }
+
+    /// Best effort; an unwritable config directory is not worth an error popup.
+    pub fn save_settings(&self) {
+        let _ = Settings { split_percent: self.split_percent, is_split_horizontal: self.is_split_horizontal, watch_mode: self.watch_mode, copy_devices: self.copy_devices }.save();
+    }
+
+    /// Turns the inactive panel into a preview of the active panel's cursor row.
+    pub fn toggle_quick_view(&mut self) {
+        self.is_quick_view = !self.is_quick_view;
+        self.is_miller = false;
+        self.quick_view.request(None);
+    }
+
+    /// Follows the cursor; the actual load is debounced in `poll_background`.
+    pub fn refresh_quick_view(&mut self) {
+        let cursor = self.cursor_path();
+        self.quick_view.request(cursor);
+    }
+
+    /// Path of the row under the active panel's cursor.
+    pub fn cursor_path(&self) -> Option<PathBuf> {
+        let (children, state) = if self.is_left_active { (&self.children_left, &self.state_left) } else { (&self.children_right, &self.state_right) };
+        children.get(state.selected()?).map(|item| item.path.clone())
+    }
+
+    /// Points the Miller parent and preview columns at the current directory and cursor; they load in `poll_background`.
+    pub fn refresh_miller(&mut self) {
+        let (dir, filter) = if self.is_left_active { (&self.dir_left, &self.filter_left) } else { (&self.dir_right, &self.filter_right) };
+        let parent = dir.parent();
+        if parent.is_some_and(|parent| self.miller_parent.as_ref().is_none_or(|cached| cached.path != parent)) {
+            self.miller_parent = parent.map(|parent| Preview::request(parent.to_path_buf(), filter));
+        } else if parent.is_none() {
+            self.miller_parent = None;
+        }
+        let cursor = self.cursor_path();
+        if cursor.as_ref() != self.miller_preview.as_ref().map(|cached| &cached.path) {
+            let filter = if self.is_left_active { &self.filter_left } else { &self.filter_right };
+            self.miller_preview = cursor.map(|path| Preview::request(path, filter));
         }
     }
 
+    pub fn open_filter(&mut self) {
+        let filter = if self.is_left_active { &self.filter_left } else { &self.filter_right };
+        self.filter_input.set(filter.mask.clone());
+        self.is_filter_displayed = true;
+    }
+
+    pub fn apply_filter(&mut self) {
+        let is_left = self.is_left_active;
+        let mask = self.filter_input.text.trim().to_string();
+        let filter = if is_left { &mut self.filter_left } else { &mut self.filter_right };
+        filter.mask = mask;
+        self.reset_filter();
+        self.clear_active_selections();
+        self.reload_panel(is_left);
+    }
+
+    pub fn reset_filter(&mut self) {
+        self.is_filter_displayed = false;
+        self.filter_input.clear();
+    }
+
     pub fn reset_rename(&mut self) {
         self.rename_input.clear();
         self.is_f2_displayed = false;
@@ -233,28 +1018,122 @@ impl AppState {
     // Quick search methods
     pub fn search_add_char(&mut self, c: char) {
         self.search_input.push(c);
-        self.jump_to_first_match();
+        self.apply_search();
     }
 
     pub fn search_backspace(&mut self) {
         self.search_input.pop();
-        if !self.search_input.is_empty() {
-            self.jump_to_first_match();
+        if self.search_input.is_empty() {
+            self.search_clear();
+        } else {
+            self.apply_search();
         }
     }
 
+    /// Clears the query and brings back rows hidden by filter mode.
     pub fn search_clear(&mut self) {
         self.search_input.clear();
+        for is_left in [true, false] {
+            let stash = if is_left { self.search_stash_left.take() } else { self.search_stash_right.take() };
+            if let Some(items) = stash {
+                self.replace_children(is_left, items);
+            }
+        }
+    }
+
+    pub fn cycle_search_mode(&mut self) {
+        self.search_mode = self.search_mode.next();
+        if !self.search_input.is_empty() {
+            self.apply_search();
+        }
+    }
+
+    pub fn toggle_search_filter(&mut self) {
+        self.is_search_filter = !self.is_search_filter;
+        if self.is_search_filter {
+            if !self.search_input.is_empty() {
+                self.apply_search();
+            }
+        } else {
+            let query = std::mem::take(&mut self.search_input);
+            self.search_clear();
+            self.search_input = query;
+            self.jump_to_first_match();
+        }
+    }
+
+    /// Score and matched char positions of `name` against the current query.
+    pub fn search_match(&self, name: &str) -> Option<(i32, Vec<usize>)> {
+        self.search_mode.match_name(&self.search_input, name)
+    }
+
+    fn apply_search(&mut self) {
+        if self.is_search_filter {
+            self.apply_search_filter();
+        }
+        self.jump_to_first_match();
+    }
+
+    /// Narrows the active panel to matching rows, stashing the full listing.
+    fn apply_search_filter(&mut self) {
+        let is_left = self.is_left_active;
+        let stash = if is_left { &mut self.search_stash_left } else { &mut self.search_stash_right };
+        let children = if is_left { &self.children_left } else { &self.children_right };
+        let all = stash.get_or_insert_with(|| children.clone());
+        let items: Vec<Item> = all.iter().filter(|item| item.name != ".." && self.search_mode.match_name(&self.search_input, item.file_name()).is_some()).cloned().collect();
+        self.replace_children(is_left, items);
+    }
+
+    /// Swaps a panel's rows, keeping the cursor and selections on the same names.
+    fn replace_children(&mut self, is_left: bool, items: Vec<Item>) {
+        self.update_children(is_left, |children| *children = items);
+    }
+
+    /// Changes a panel's rows in place through `update`, keeping the cursor and selections on the same entries.
+    fn update_children(&mut self, is_left: bool, update: impl FnOnce(&mut Vec<Item>)) {
+        let (children, state, selected_set) = if is_left {
+            (&mut self.children_left, &mut self.state_left, &mut self.selected_left)
+        } else {
+            (&mut self.children_right, &mut self.state_right, &mut self.selected_right)
+        };
+        let current = state.selected().and_then(|index| children.get(index)).map(|item| item.path.clone());
+        let selected_paths: HashSet<PathBuf> = selected_set.iter().filter_map(|&index| children.get(index)).map(|item| item.path.clone()).collect();
+        update(children);
+        *selected_set = children.iter().enumerate().filter(|(_, item)| selected_paths.contains(&item.path)).map(|(index, _)| index).collect();
+        // A vanished cursor row leaves the cursor where it was, on whatever moved up into its place
+        let fallback = state.selected().unwrap_or(0).min(children.len().saturating_sub(1));
+        let index = current.and_then(|path| children.iter().position(|item| item.path == path)).unwrap_or(fallback);
+        state.select(Some(index));
+        self.count_unreadable(is_left);
     }
 
+    /// Counts the listing's unreadable rows for the path bar, once per change rather than on every frame.
+    fn count_unreadable(&mut self, is_left: bool) {
+        let (rows, count) = if is_left {
+            (self.search_stash_left.as_ref().unwrap_or(&self.children_left), &mut self.unreadable_left)
+        } else {
+            (self.search_stash_right.as_ref().unwrap_or(&self.children_right), &mut self.unreadable_right)
+        };
+        *count = rows.iter().filter(|item| item.error.is_some()).count();
+    }
+
+    /// Indices of active panel rows matching the query, with their scores.
+    fn search_hits(&self) -> Vec<(usize, i32)> {
+        let children = if self.is_left_active { &self.children_left } else { &self.children_right };
+        children.iter().enumerate()
+            .filter_map(|(index, item)| self.search_match(item.file_name()).map(|(score, _)| (index, score)))
+            .collect()
+    }
+
+    /// Fuzzy mode goes to the best-scoring row, other modes to the first.
     pub fn jump_to_first_match(&mut self) {
         if self.search_input.is_empty() {
             return;
         }
-        let search_lower = self.search_input.to_lowercase();
-        let (children, state) = self.active_panel_mut();
-        if let Some(index) = children.iter().position(|item| item.name_full.to_lowercase().starts_with(&search_lower)) {
-            state.select(Some(index));
+        let hits = self.search_hits();
+        let best = if self.search_mode == SearchMode::Fuzzy { hits.iter().min_by_key(|&&(index, score)| (-score, index)) } else { hits.first() };
+        if let Some(&(index, _)) = best {
+            self.active_panel_mut().1.select(Some(index));
         }
     }
 
@@ -262,18 +1141,13 @@ impl AppState {
         if self.search_input.is_empty() {
             return;
         }
-        let search_lower = self.search_input.to_lowercase();
-        let (children, state) = self.active_panel_mut();
+        let hits = self.search_hits();
+        let state = self.active_panel_mut().1;
         let current = state.selected().unwrap_or(0);
-        let len = children.len();
 
         // Search forward from current+1, wrapping around
-        for offset in 1..=len {
-            let i = (current + offset) % len;
-            if children[i].name_full.to_lowercase().starts_with(&search_lower) {
-                state.select(Some(i));
-                return;
-            }
+        if let Some(&(index, _)) = hits.iter().find(|&&(index, _)| index > current).or(hits.first()) {
+            state.select(Some(index));
         }
     }
 
@@ -281,18 +1155,13 @@ impl AppState {
         if self.search_input.is_empty() {
             return;
         }
-        let search_lower = self.search_input.to_lowercase();
-        let (children, state) = self.active_panel_mut();
+        let hits = self.search_hits();
+        let state = self.active_panel_mut().1;
         let current = state.selected().unwrap_or(0);
-        let len = children.len();
 
         // Search backward from current-1, wrapping around
-        for offset in 1..=len {
-            let i = (current + len - offset) % len;
-            if children[i].name_full.to_lowercase().starts_with(&search_lower) {
-                state.select(Some(i));
-                return;
-            }
+        if let Some(&(index, _)) = hits.iter().rev().find(|&&(index, _)| index < current).or(hits.last()) {
+            state.select(Some(index));
         }
     }
 
@@ -305,6 +1174,390 @@ impl AppState {
         }
     }
 
+    /// Shows the previous results if a search is still around, otherwise a fresh form.
+    pub fn open_find(&mut self) {
+        if self.find_search.is_none() {
+            let dir = if self.is_left_active { &self.dir_left } else { &self.dir_right };
+            self.find_dialog.start_dir.set(dir.display().to_string());
+        }
+        self.is_find_displayed = true;
+    }
+
+    pub fn start_find(&mut self) {
+        match self.find_dialog.criteria() {
+            Ok(criteria) => self.find_search = Some(FindSearch::start(criteria)),
+            Err(e) => self.display_error(e),
+        }
+    }
+
+    /// Opens the selected result in the viewer (or editor), keeping the results for Alt+F7.
+    pub fn open_find_result(&mut self, edit: bool) {
+        let Some(result) = self.find_search.as_ref().and_then(|search| search.selected_result()) else { return };
+        if let Some(member) = &result.archive_entry {
+            let message = format!("{} is inside {}; Enter goes to the archive", member, display_name(result.path.as_os_str()));
+            self.display_error(message);
+            return;
+        }
+        if result.path.is_dir() {
+            return;
+        }
+        let (path, line, range) = (result.path.clone(), result.line.unwrap_or(0), result.match_range.clone());
+        self.is_find_displayed = false;
+        let opened = if edit { self.open_editor_at(path, line, range.start) } else { self.open_viewer_at(path, line, range) };
+        if let Err(e) = opened {
+            self.display_error(e);
+        }
+    }
+
+    /// Back from the result list to the form, stopping the search.
+    pub fn stop_find(&mut self) {
+        self.find_search = None;
+    }
+
+    pub fn reset_find(&mut self) {
+        self.is_find_displayed = false;
+        self.find_search = None;
+    }
+
+    /// Points the active panel at `path`'s directory with the cursor on it.
+    pub fn go_to_path(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else { return };
+        self.open_dir(self.is_left_active, parent.to_path_buf(), Some(path.to_path_buf()));
+    }
+
+    pub fn open_compare(&mut self) {
+        self.compare_message.clear();
+        self.is_compare_displayed = true;
+    }
+
+    pub fn reset_compare(&mut self) {
+        self.is_compare_displayed = false;
+        self.dir_compare = None;
+        self.compare_message.clear();
+    }
+
+    /// Toggles the option under the cursor in the compare popup.
+    pub fn toggle_compare_option(&mut self) {
+        let options = &mut self.compare_options;
+        if self.compare_cursor == 0 {
+            options.method = if options.method == CompareMethod::Quick { CompareMethod::Content } else { CompareMethod::Quick };
+        } else {
+            options.recursive = !options.recursive;
+        }
+    }
+
+    /// Compares the top-level entries of both panels in the background.
+    pub fn start_compare(&mut self) {
+        let top_level = |children: &[Item]| -> Vec<PathBuf> { children.iter().filter(|item| item.depth == 0 && item.name != "..").map(|item| item.path.clone()).collect() };
+        self.compare_message.clear();
+        self.dir_compare = Some(DirCompare::start(top_level(&self.children_left), top_level(&self.children_right), self.compare_options));
+    }
+
+    /// Marks differing entries and selects the ones worth copying across.
+    fn apply_compare(&mut self, result: CompareResult) {
+        if result.left.is_empty() && result.right.is_empty() {
+            self.compare_message = "No differences found".to_string();
+            return;
+        }
+        let selected = |children: &[Item], flags: &HashMap<PathBuf, CompareFlag>| -> HashSet<usize> {
+            children.iter().enumerate().filter(|(_, item)| item.depth == 0 && flags.get(&item.path).is_some_and(|flag| flag.selects(item.is_dir))).map(|(index, _)| index).collect()
+        };
+        self.selected_left = selected(&self.children_left, &result.left);
+        self.selected_right = selected(&self.children_right, &result.right);
+        self.compare_left = result.left;
+        self.compare_right = result.right;
+        self.is_compare_displayed = false;
+    }
+
+    pub fn refresh_space(&mut self) {
+        self.space_left.refresh(&self.dir_left);
+        self.space_right.refresh(&self.dir_right);
+    }
+
+    /// Lists mounted filesystems for switching the given panel, with the cursor on the one it is on.
+    pub fn open_mounts(&mut self, is_left: bool) {
+        match list_mounts() {
+            Ok(mounts) => {
+                let dir = if is_left { &self.dir_left } else { &self.dir_right };
+                // The longest mount point containing the directory is the one it lives on
+                self.mounts_selected = mounts.iter().enumerate().filter(|(_, mount)| dir.starts_with(&mount.mount_point)).max_by_key(|(_, mount)| mount.mount_point.as_os_str().len()).map_or(0, |(index, _)| index);
+                self.mount_spaces = Some(MountSpaces::start(mounts.iter().map(|mount| mount.mount_point.clone()).collect()));
+                self.mounts = mounts;
+                self.mounts_for_left = is_left;
+                self.is_mounts_displayed = true;
+            }
+            Err(e) => self.display_error(e),
+        }
+    }
+
+    pub fn reset_mounts(&mut self) {
+        self.is_mounts_displayed = false;
+        self.mounts.clear();
+        self.mount_spaces = None;
+    }
+
+    /// Fills in mount sizes as they arrive; pseudo filesystems without a size (or none at all) leave the list.
+    fn poll_mount_spaces(&mut self) {
+        let Some(spaces) = &self.mount_spaces else { return };
+        for (mount_point, space) in spaces.poll() {
+            let Some(index) = self.mounts.iter().position(|mount| mount.mount_point == mount_point) else { continue };
+            match space.filter(|space| space.total > 0) {
+                Some(space) => self.mounts[index].space = Some(space),
+                None => {
+                    self.mounts.remove(index);
+                    // The cursor stays on the same mount
+                    if index < self.mounts_selected {
+                        self.mounts_selected -= 1;
+                    }
+                    self.mounts_selected = self.mounts_selected.min(self.mounts.len().saturating_sub(1));
+                }
+            }
+        }
+    }
+
+    /// Points the chosen panel at the selected mount point and activates it.
+    pub fn go_to_mount(&mut self) {
+        let Some(mount_point) = self.mounts.get(self.mounts_selected).map(|mount| mount.mount_point.clone()) else { return };
+        let is_left = self.mounts_for_left;
+        self.reset_mounts();
+        self.is_left_active = is_left;
+        self.open_dir(is_left, mount_point, None);
+    }
+
+    /// Opens the disk usage view on the active panel's directory, reusing a cached scan that covers it.
+    pub fn open_disk_usage(&mut self) {
+        let dir = if self.is_left_active { self.dir_left.clone() } else { self.dir_right.clone() };
+        self.is_du_displayed = true;
+        match self.du_cache.iter().position(|tree| tree.contains(&dir)) {
+            Some(index) => self.du_view = Some(UsageView::new(self.du_cache.remove(index), dir)),
+            None => self.du_scan = Some(UsageScan::start(dir)),
+        }
+    }
+
+    /// Throws away the scan being browsed and scans its current directory again.
+    pub fn rescan_disk_usage(&mut self) {
+        let Some(view) = self.du_view.take() else { return };
+        self.du_scan = Some(UsageScan::start(view.dir));
+    }
+
+    /// Stops a running scan; a finished one goes into the cache.
+    pub fn close_disk_usage(&mut self) {
+        self.is_du_displayed = false;
+        self.du_scan = None;
+        let Some(view) = self.du_view.take() else { return };
+        self.du_cache.retain(|tree| !tree.root.starts_with(&view.tree.root));
+        if self.du_cache.len() >= CACHE_SIZE {
+            self.du_cache.remove(0);
+        }
+        self.du_cache.push(view.tree);
+    }
+
+    pub fn du_delete_confirm(&mut self) {
+        let Some(view) = &mut self.du_view else { return };
+        let result = view.delete_selected();
+        for is_left in [true, false] {
+            self.reload_panel(is_left);
+        }
+        if let Err(e) = result {
+            self.display_error(format!("Cannot delete: {}", e));
+        }
+    }
+
+    /// Sync makes the right directory match the left one, so both panels must show plain directories.
+    pub fn open_sync(&mut self) {
+        if self.list_left.is_some() || self.list_right.is_some() {
+            self.display_error("Sync works on directories, not list panels".to_string());
+        } else if self.dir_left.starts_with(&self.dir_right) || self.dir_right.starts_with(&self.dir_left) {
+            self.display_error("Cannot sync a directory with itself or a subdirectory".to_string());
+        } else {
+            self.is_sync_displayed = true;
+        }
+    }
+
+    pub fn reset_sync(&mut self) {
+        self.is_sync_displayed = false;
+        self.sync_plan = None;
+        self.sync_export = None;
+    }
+
+    pub fn start_sync_plan(&mut self) {
+        self.sync_plan = Some(SyncPlan::start(self.dir_left.clone(), self.dir_right.clone(), &self.sync_dialog));
+    }
+
+    /// Back from the plan to the options.
+    pub fn stop_sync_plan(&mut self) {
+        self.sync_plan = None;
+        self.sync_export = None;
+    }
+
+    /// Starts executing the enabled actions of a finished plan; `finish_sync` follows once they ran.
+    pub fn run_sync(&mut self) {
+        let Some(plan) = self.sync_plan.as_mut().filter(|plan| plan.done && plan.run.is_none()) else { return };
+        plan.execute(self.copy_devices);
+    }
+
+    /// Closes the dialog after a sync ran (or was stopped) and reloads both panels.
+    fn finish_sync(&mut self) {
+        let Some(mut run) = self.sync_plan.as_mut().and_then(|plan| plan.run.take()) else { return };
+        self.reset_sync();
+        for is_left in [true, false] {
+            self.reload_panel(is_left);
+        }
+        self.clear_all_selections();
+        if let Some(e) = run.error.take() {
+            self.display_error(e);
+        } else if run.completed < run.total {
+            self.display_error(format!("Sync stopped after {} of {} actions", run.completed, run.total));
+        }
+    }
+
+    pub fn open_sync_export(&mut self) {
+        let Some(plan) = &self.sync_plan else { return };
+        let default = plan.left_root.parent().unwrap_or(&plan.left_root).join("sync-plan.txt");
+        let mut input = TextInput::new();
+        input.set(default.display().to_string());
+        self.sync_export = Some(input);
+    }
+
+    /// Writes the plan to the typed path; relative paths resolve against the left directory.
+    pub fn save_sync_export(&mut self) {
+        let (Some(plan), Some(input)) = (&mut self.sync_plan, self.sync_export.take()) else { return };
+        let path = plan.left_root.join(input.text.trim());
+        match plan.export(&path) {
+            Ok(()) => plan.note = format!("Plan saved to {}", path.display()),
+            Err(e) => self.display_error(format!("Cannot write {}: {}", path.display(), e)),
+        }
+    }
+
+    /// The row under the active panel's cursor.
+    pub fn cursor_item(&self) -> Option<&Item> {
+        let (children, state) = if self.is_left_active { (&self.children_left, &self.state_left) } else { (&self.children_right, &self.state_right) };
+        state.selected().and_then(|index| children.get(index))
+    }
+
+    pub fn is_list_active(&self) -> bool {
+        if self.is_left_active { self.list_left.is_some() } else { self.list_right.is_some() }
+    }
+
+    pub fn open_panelize(&mut self) {
+        self.panelize_input.clear();
+        self.is_panelize_displayed = true;
+    }
+
+    pub fn reset_panelize(&mut self) {
+        self.is_panelize_displayed = false;
+        self.panelize_input.clear();
+        self.panelize_run = None;
+    }
+
+    /// Runs the command (or reads the `<file`) typed in the panelize popup in the background.
+    pub fn apply_panelize(&mut self) {
+        let dir = if self.is_left_active { &self.dir_left } else { &self.dir_right };
+        self.panelize_run = Some(PanelizeRun::start(self.panelize_input.text.clone(), dir.clone()));
+    }
+
+    /// Shows the collected paths in the active panel once the panelize command is done.
+    fn poll_panelize(&mut self) {
+        let Some(result) = self.panelize_run.as_ref().and_then(PanelizeRun::poll) else { return };
+        self.reset_panelize();
+        match result {
+            Ok(list) => self.panelize(list),
+            Err(e) => self.display_error(e),
+        }
+    }
+
+    /// Shows the find results as a list in the active panel.
+    pub fn panelize_find_results(&mut self) {
+        let Some(search) = &self.find_search else { return };
+        let mut paths: Vec<PathBuf> = Vec::with_capacity(search.results.len());
+        // Text matches report one result per line
+        for result in &search.results {
+            if paths.last() != Some(&result.path) {
+                paths.push(result.path.clone());
+            }
+        }
+        if paths.is_empty() {
+            return;
+        }
+        let title = format!("find in {}", display_name(search.start_dir.as_os_str()));
+        self.reset_find();
+        self.panelize(PanelList { title, paths });
+    }
+
+    /// Replaces the active panel's listing with `list`; ".." or Backspace goes back.
+    pub fn panelize(&mut self, list: PanelList) {
+        let is_left = self.is_left_active;
+        if is_left {
+            self.list_left = Some(list);
+            self.compare_left.clear();
+        } else {
+            self.list_right = Some(list);
+            self.compare_right.clear();
+        }
+        self.search_clear();
+        self.clear_active_selections();
+        let items = self.load_list_panel_rows(is_left);
+        let first = usize::from(items.len() > 1);
+        if is_left {
+            self.children_left = items;
+        } else {
+            self.children_right = items;
+        }
+        self.count_unreadable(is_left);
+        self.active_panel_mut().1.select(Some(first));
+    }
+
+    /// Leaves the active panel's list and shows its directory again.
+    pub fn close_list(&mut self) {
+        let is_left = self.is_left_active;
+        if is_left {
+            self.list_left = None;
+        } else {
+            self.list_right = None;
+        }
+        let dir = if is_left { self.dir_left.clone() } else { self.dir_right.clone() };
+        self.open_dir(is_left, dir, None);
+    }
+
+    /// Keeps a renamed entry in the active panel's list.
+    pub fn rename_in_list(&mut self, original: &Path, renamed: &Path) {
+        let list = if self.is_left_active { &mut self.list_left } else { &mut self.list_right };
+        if let Some(list) = list {
+            for path in list.paths.iter_mut().filter(|path| path.as_path() == original) {
+                *path = renamed.to_path_buf();
+            }
+        }
+    }
+
+    /// Starts indexing the active panel's directory for the Ctrl+P popup.
+    pub fn open_fuzzy(&mut self) {
+        let dir = if self.is_left_active { &self.dir_left } else { &self.dir_right };
+        self.fuzzy_finder = Some(FuzzyFinder::start(dir.clone()));
+        self.is_fuzzy_displayed = true;
+    }
+
+    pub fn reset_fuzzy(&mut self) {
+        self.is_fuzzy_displayed = false;
+        self.fuzzy_finder = None;
+    }
+
+    /// Jumps to, views or edits the selected fuzzy match and closes the popup.
+    pub fn open_fuzzy_result(&mut self, action: FuzzyAction) {
+        let Some(path) = self.fuzzy_finder.as_ref().and_then(|finder| finder.selected_path()) else { return };
+        self.reset_fuzzy();
+        let opened = match action {
+            FuzzyAction::GoTo => {
+                self.go_to_path(&path);
+                Ok(())
+            }
+            FuzzyAction::View => self.open_viewer(path),
+            FuzzyAction::Edit => self.open_editor(path),
+        };
+        if let Err(e) = opened {
+            self.display_error(e);
+        }
+    }
+
     pub fn reset_delete(&mut self) {
         self.is_f8_displayed = false;
         self.delete_items.clear();
@@ -318,14 +1571,29 @@ impl AppState {
         Ok(())
     }
 
+    /// Opens the viewer scrolled to `line` with `range` of that line highlighted.
+    pub fn open_viewer_at(&mut self, file_path: PathBuf, line: usize, range: Range<usize>) -> Result<(), String> {
+        self.open_viewer(file_path)?;
+        // Not rendered yet on first open, the panel height is a close estimate
+        let viewport = if self.viewer_viewport_height > 0 { self.viewer_viewport_height } else { self.page_size as usize };
+        if let Some(state) = &mut self.viewer_state && !state.is_binary {
+            // The line may not be indexed yet
+            let max = state.total_lines.max(line + viewport).saturating_sub(viewport);
+            state.scroll_offset = line.saturating_sub(viewport / 2).min(max);
+            state.highlight = Some((line, range));
+        }
+        Ok(())
+    }
+
     pub fn close_viewer(&mut self) {
         self.is_f3_displayed = false;
+        self.is_viewer_goto_displayed = false;
         self.viewer_state = None;
     }
 
     pub fn viewer_scroll_down(&mut self) {
         if let Some(state) = &mut self.viewer_state {
-            let max = state.total_lines.saturating_sub(self.viewer_viewport_height);
+            let max = state.line_count().saturating_sub(self.viewer_viewport_height);
             state.scroll_offset = (state.scroll_offset + 1).min(max);
         }
     }
@@ -338,7 +1606,7 @@ impl AppState {
 
     pub fn viewer_page_down(&mut self) {
         if let Some(state) = &mut self.viewer_state {
-            let max = state.total_lines.saturating_sub(self.viewer_viewport_height);
+            let max = state.line_count().saturating_sub(self.viewer_viewport_height);
             state.scroll_offset = (state.scroll_offset + self.viewer_viewport_height).min(max);
         }
     }
@@ -370,17 +1638,47 @@ impl AppState {
 
     pub fn viewer_end(&mut self) {
         if let Some(state) = &mut self.viewer_state {
-            state.scroll_offset = state.total_lines.saturating_sub(self.viewer_viewport_height);
+            state.scroll_offset = state.line_count().saturating_sub(self.viewer_viewport_height);
+        }
+    }
+
+    /// Switches the viewer between text and hex dump.
+    pub fn viewer_toggle_mode(&mut self) {
+        if let Some(state) = &mut self.viewer_state {
+            state.toggle_mode();
+        }
+    }
+
+    /// Starts the hex editor on the viewed file, switching to hex first.
+    pub fn viewer_start_hex_edit(&mut self) {
+        if let Some(state) = &mut self.viewer_state {
+            state.start_hex_edit();
+        }
+    }
+
+    pub fn open_viewer_goto(&mut self) {
+        self.viewer_goto_input.clear();
+        self.is_viewer_goto_displayed = true;
+    }
+
+    /// Jumps to the offset or line typed in the go-to prompt.
+    pub fn viewer_goto(&mut self) {
+        self.is_viewer_goto_displayed = false;
+        let viewport = self.viewer_viewport_height;
+        if let Some(state) = &mut self.viewer_state && let Err(e) = state.go_to(&self.viewer_goto_input.text, viewport) {
+            self.display_error(e);
         }
     }
 
     pub fn open_editor(&mut self, file_path: PathBuf) -> Result<(), String> {
         use crate::viewer::{highlight_content, is_binary_file};
 
-        if is_binary_file(&file_path).unwrap_or(false) {
+        if is_binary_file(&file_path).map_err(|e| e.to_string())? {
             self.open_viewer(file_path)?;
+            // Binary files get the hex editor
             if let Some(state) = &mut self.viewer_state {
                 state.from_edit = true;
+                state.start_hex_edit();
             }
             return Ok(());
         }
@@ -411,9 +1709,32 @@ impl AppState {
         Ok(())
     }
 
+    /// Opens the editor with the cursor at byte `column` of `line`.
+    pub fn open_editor_at(&mut self, file_path: PathBuf, line: usize, column: usize) -> Result<(), String> {
+        self.open_editor(file_path)?;
+        let viewport = if self.editor_viewport_height > 0 { self.editor_viewport_height } else { self.page_size as usize };
+        if let Some(state) = &mut self.editor_state {
+            state.cursor_line = line.min(state.lines.len().saturating_sub(1));
+            let text = &state.lines[state.cursor_line];
+            state.cursor_col = text.get(..column).map(|before| before.chars().count()).unwrap_or(0);
+            state.scroll_offset = state.cursor_line.saturating_sub(viewport / 2);
+        }
+        Ok(())
+    }
+
+    /// Closes the text editor, or the hex editor if that is open: back to the viewer when editing started there.
     pub fn close_editor(&mut self) {
         self.is_f4_displayed = false;
         self.editor_state = None;
+        match &mut self.viewer_state {
+            Some(state) if state.hex_edit.is_some() && !state.from_edit => state.hex_edit = None,
+            Some(state) if state.hex_edit.is_some() => self.close_viewer(),
+            _ => {}
+        }
+    }
+
+    pub fn is_hex_editing(&self) -> bool {
+        self.viewer_state.as_ref().is_some_and(|state| state.hex_edit.is_some())
     }
 
     pub fn editor_rehighlight(&mut self) {
@@ -424,11 +1745,9 @@ impl AppState {
     }
 
     pub fn editor_scroll_up(&mut self) {
-        if let Some(state) = &mut self.editor_state {
-            if state.scroll_offset > 0 {
-                state.scroll_offset -= 1;
-                state.auto_scroll = false;
-            }
+        if let Some(state) = &mut self.editor_state && state.scroll_offset > 0 {
+            state.scroll_offset -= 1;
+            state.auto_scroll = false;
         }
     }
 
@@ -457,25 +1776,21 @@ impl AppState {
     }
 
     pub fn editor_cursor_up(&mut self) {
-        if let Some(state) = &mut self.editor_state {
-            if state.cursor_line > 0 {
-                state.cursor_line -= 1;
-                state.clamp_col();
-                if state.cursor_line < state.scroll_offset {
-                    state.scroll_offset = state.cursor_line;
-                }
+        if let Some(state) = &mut self.editor_state && state.cursor_line > 0 {
+            state.cursor_line -= 1;
+            state.clamp_col();
+            if state.cursor_line < state.scroll_offset {
+                state.scroll_offset = state.cursor_line;
             }
         }
     }
 
     pub fn editor_cursor_down(&mut self) {
-        if let Some(state) = &mut self.editor_state {
-            if state.cursor_line < state.lines.len().saturating_sub(1) {
-                state.cursor_line += 1;
-                state.clamp_col();
-                if state.cursor_line >= state.scroll_offset + self.editor_viewport_height {
-                    state.scroll_offset = state.cursor_line - self.editor_viewport_height + 1;
-                }
+        if let Some(state) = &mut self.editor_state && state.cursor_line < state.lines.len().saturating_sub(1) {
+            state.cursor_line += 1;
+            state.clamp_col();
+            if state.cursor_line >= state.scroll_offset + self.editor_viewport_height {
+                state.scroll_offset = state.cursor_line - self.editor_viewport_height + 1;
             }
         }
     }
@@ -621,6 +1936,9 @@ impl AppState {
     }
 
     pub fn editor_save(&mut self) -> Result<(), String> {
+        if let Some(state) = &mut self.viewer_state && state.hex_edit.is_some() {
+            state.save_hex().map_err(|e| e.to_string())?;
+        }
         if let Some(state) = &mut self.editor_state {
             let content = state.lines.join("\n");
             std::fs::write(&state.file_path, content).map_err(|e| e.to_string())?;
@@ -630,12 +1948,14 @@ impl AppState {
     }
 
     pub fn editor_is_modified(&self) -> bool {
-        self.editor_state.as_ref().is_some_and(|s| s.modified)
+        self.editor_state.as_ref().is_some_and(|s| s.modified) || self.viewer_state.as_ref().is_some_and(|s| s.is_hex_modified())
     }
 
     pub fn reset_copy(&mut self) {
         self.is_f5_displayed = false;
         self.copy_items.clear();
+        self.copy_overwrites = 0;
+        self.is_compare_copy = false;
     }
 
     pub fn reset_move(&mut self) {
@@ -658,10 +1978,10 @@ impl AppState {
         let mut dir_size_result: Option<(PathBuf, u64)> = None;
 
         {
-            let (state, children, selected_set, current_dir) = if self.is_left_active {
-                (&mut self.state_left, &self.children_left, &mut self.selected_left, &self.dir_left)
+            let (state, children, selected_set) = if self.is_left_active {
+                (&mut self.state_left, &self.children_left, &mut self.selected_left)
             } else {
-                (&mut self.state_right, &self.children_right, &mut self.selected_right, &self.dir_right)
+                (&mut self.state_right, &self.children_right, &mut self.selected_right)
             };
 
             if let Some(index) = state.selected() {
@@ -672,7 +1992,7 @@ impl AppState {
                         selected_set.insert(index);
 
                         if calculate_size && item.is_dir {
-                            let full_path = current_dir.join(&item.name_full);
+                            let full_path = item.path.clone();
                             match calculate_dir_size(&full_path) {
                                 Ok(size) => dir_size_result = Some((full_path, size)),
                                 Err(e) => error_msg = Some(format!("Cannot calculate size: {}", e)),
@@ -0,0 +1,96 @@
+use crate::fs_ops::open_regular_file;
+use chrono::{Local, TimeZone};
+use flate2::read::GzDecoder;
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::ZipArchive;
+
+#[derive(Clone, Copy)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Archive type by file name; only formats readable without external tools.
+fn archive_kind(name: &str) -> Option<ArchiveKind> {
+    let name = name.to_ascii_lowercase();
+    if name.ends_with(".zip") || name.ends_with(".jar") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(name: &str) -> bool {
+    archive_kind(name).is_some()
+}
+
+/// One member of an archive.
+pub struct ArchiveEntry {
+    /// Path inside the archive, `/`-separated, without leading or trailing slashes.
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl ArchiveEntry {
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Calls `visit` with every member of the archive at `path` and a reader of its content, until it returns false.
+/// Zip members that cannot be read (encrypted, unsupported compression) are skipped.
+pub fn for_each_entry(path: &Path, mut visit: impl FnMut(&ArchiveEntry, &mut dyn Read) -> bool) -> Result<(), Error> {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let Some(kind) = archive_kind(&name) else { return Err(Error::new(ErrorKind::InvalidInput, "Not an archive")) };
+    let reader = BufReader::new(open_regular_file(path)?);
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(reader).map_err(Error::other)?;
+            for index in 0..archive.len() {
+                let Ok(mut member) = archive.by_index(index) else { continue };
+                let entry = ArchiveEntry {
+                    path: member.name().trim_matches('/').to_string(),
+                    is_dir: member.is_dir(),
+                    size: member.size(),
+                    // Zip times carry no zone and are local by convention
+                    modified: member.last_modified().and_then(|time| {
+                        let local = Local.with_ymd_and_hms(time.year().into(), time.month().into(), time.day().into(), time.hour().into(), time.minute().into(), time.second().into());
+                        local.single().map(SystemTime::from)
+                    }),
+                };
+                if !entry.path.is_empty() && !visit(&entry, &mut member) {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        ArchiveKind::Tar => visit_tar(tar::Archive::new(reader), visit),
+        ArchiveKind::TarGz => visit_tar(tar::Archive::new(GzDecoder::new(reader)), visit),
+    }
+}
+
+fn visit_tar<R: Read>(mut archive: tar::Archive<R>, mut visit: impl FnMut(&ArchiveEntry, &mut dyn Read) -> bool) -> Result<(), Error> {
+    for member in archive.entries()? {
+        let mut member = member?;
+        let header = member.header();
+        let entry = ArchiveEntry {
+            path: member.path()?.to_string_lossy().trim_start_matches("./").trim_matches('/').to_string(),
+            is_dir: header.entry_type().is_dir(),
+            size: header.size()?,
+            modified: header.mtime().ok().map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds)),
+        };
+        if !entry.path.is_empty() && !visit(&entry, &mut member) {
+            break;
+        }
+    }
+    Ok(())
+}
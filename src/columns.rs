@@ -0,0 +1,171 @@
+use crate::app::Item;
+use crate::fs_ops::format_time_opt;
+use crate::utils::format_size;
+
+/// How a panel lists its rows (Alt+V cycles).
+#[derive(Clone, Copy, PartialEq)]
+pub enum ViewMode {
+    /// The classic Name / Ext / Size / Modified table.
+    Normal,
+    /// Names only, flowing through several columns.
+    Brief,
+    /// A table of user-chosen columns (Alt+C).
+    Full,
+}
+
+impl ViewMode {
+    pub fn next(self) -> Self {
+        match self {
+            ViewMode::Normal => ViewMode::Brief,
+            ViewMode::Brief => ViewMode::Full,
+            ViewMode::Full => ViewMode::Normal,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewMode::Normal => "normal",
+            ViewMode::Brief => "brief",
+            ViewMode::Full => "full",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Column {
+    Name,
+    Ext,
+    Size,
+    DirSize,
+    Modified,
+    Accessed,
+    Changed,
+    Permissions,
+    Owner,
+    Group,
+    Inode,
+    Links,
+}
+
+impl Column {
+    pub const ALL: [Column; 12] = [
+        Column::Name,
+        Column::Ext,
+        Column::Size,
+        Column::DirSize,
+        Column::Modified,
+        Column::Accessed,
+        Column::Changed,
+        Column::Permissions,
+        Column::Owner,
+        Column::Group,
+        Column::Inode,
+        Column::Links,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Ext => "Ext",
+            Column::Size => "Size",
+            Column::DirSize => "Dir size",
+            Column::Modified => "Modified",
+            Column::Accessed => "Accessed",
+            Column::Changed => "Changed",
+            Column::Permissions => "Permissions",
+            Column::Owner => "Owner",
+            Column::Group => "Group",
+            Column::Inode => "Inode",
+            Column::Links => "Links",
+        }
+    }
+
+    fn default_width(self) -> u16 {
+        match self {
+            Column::Name => 12,
+            Column::Ext => 6,
+            Column::Size | Column::DirSize => 9,
+            Column::Modified | Column::Accessed | Column::Changed => 14,
+            Column::Permissions => 10,
+            Column::Owner | Column::Group => 8,
+            Column::Inode => 9,
+            Column::Links => 5,
+        }
+    }
+
+    /// Cell text for every column except Name, which the panel renders itself.
+    /// `dir_size` is the calculated size of a directory row, if known.
+    pub fn cell_text(self, item: &Item, dir_size: Option<u64>) -> String {
+        let details = &item.details;
+        let is_parent = item.name == "..";
+        match self {
+            Column::Name => item.name_full.clone(),
+            Column::Ext => item.extension.clone(),
+            Column::Size => item.size_text(dir_size),
+            Column::DirSize if item.is_dir && !is_parent => dir_size.map(format_size).unwrap_or_else(|| "?".to_string()),
+            Column::DirSize => String::new(),
+            Column::Modified => format_time_opt(item.modified),
+            Column::Accessed => format_time_opt(details.accessed),
+            Column::Changed => format_time_opt(details.changed),
+            Column::Permissions => details.permissions(),
+            Column::Owner => details.owner(),
+            Column::Group => details.group(),
+            Column::Inode if !is_parent => details.inode.to_string(),
+            Column::Links if !is_parent => details.links.to_string(),
+            Column::Inode | Column::Links => String::new(),
+        }
+    }
+}
+
+pub struct ColumnSetting {
+    pub column: Column,
+    pub enabled: bool,
+    pub width: u16,
+}
+
+/// Per-panel view mode plus the ordered column list used by full mode.
+pub struct PanelView {
+    pub mode: ViewMode,
+    pub columns: Vec<ColumnSetting>,
+}
+
+impl PanelView {
+    pub fn new() -> Self {
+        let enabled = [Column::Name, Column::Size, Column::Permissions, Column::Owner, Column::Group];
+        // Enabled columns first in their default order, the rest after
+        let mut columns: Vec<ColumnSetting> = enabled.iter().map(|&column| ColumnSetting { column, enabled: true, width: column.default_width() }).collect();
+        columns.extend(Column::ALL.iter().filter(|column| !enabled.contains(column)).map(|&column| ColumnSetting { column, enabled: false, width: column.default_width() }));
+        Self { mode: ViewMode::Normal, columns }
+    }
+
+    pub fn enabled_columns(&self) -> impl Iterator<Item = &ColumnSetting> {
+        self.columns.iter().filter(|setting| setting.enabled)
+    }
+
+    /// Without an Ext column, full mode shows names with their extension.
+    pub fn shows_extension_in_name(&self) -> bool {
+        self.mode == ViewMode::Full && !self.enabled_columns().any(|setting| setting.column == Column::Ext)
+    }
+
+    /// The Name column always stays visible.
+    pub fn toggle_column(&mut self, index: usize) {
+        if let Some(setting) = self.columns.get_mut(index) && setting.column != Column::Name {
+            setting.enabled = !setting.enabled;
+        }
+    }
+
+    /// Swaps the column with its neighbour, returns the new index.
+    pub fn move_column(&mut self, index: usize, delta: isize) -> usize {
+        let target = index.saturating_add_signed(delta).min(self.columns.len().saturating_sub(1));
+        if index < self.columns.len() {
+            self.columns.swap(index, target);
+        }
+        target
+    }
+
+    pub fn resize_column(&mut self, index: usize, delta: i16) {
+        if let Some(setting) = self.columns.get_mut(index) {
+            setting.width = setting.width.saturating_add_signed(delta).clamp(3, 60);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::fs_ops::open_regular_file;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{Metadata, read_dir, symlink_metadata};
+use std::io::{Error, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// FAT and exFAT (typical on USB drives) store times with 2 second resolution.
+const TIME_TOLERANCE: Duration = Duration::from_secs(2);
+const PROGRESS_INTERVAL: usize = 256;
+
+/// Why an entry differs from its namesake in the other panel.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompareFlag {
+    /// Missing on the other side.
+    OnlyHere,
+    Newer,
+    /// Older, but bigger than the newer copy on the other side.
+    Larger,
+    /// Same time (or a file against a directory) but different size or content.
+    Different,
+    /// A directory on both sides with something different inside.
+    Inside,
+}
+
+impl CompareFlag {
+    /// Marker drawn in the icon column.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompareFlag::OnlyHere => "+ ",
+            CompareFlag::Newer => "↑ ",
+            CompareFlag::Larger => "! ",
+            CompareFlag::Different => "≠ ",
+            CompareFlag::Inside => "≈ ",
+        }
+    }
+
+    /// Whether the entry is selected for copying to the other side.
+    /// An older but larger file is only flagged, overwriting the newer one is a judgment call. A directory with
+    /// differences inside is selected too, copying it brings over only the flagged entries below it.
+    pub fn selects(self, is_dir: bool) -> bool {
+        match self {
+            CompareFlag::OnlyHere | CompareFlag::Inside => true,
+            CompareFlag::Newer | CompareFlag::Different => !is_dir,
+            CompareFlag::Larger => false,
+        }
+    }
+}
+
+/// The flagged entries below `dir` that copying it after a compare brings across, skipping directories whose
+/// own flagged entries are listed.
+pub fn entries_to_copy(flags: &HashMap<PathBuf, CompareFlag>, dir: &Path) -> Vec<(PathBuf, bool)> {
+    let mut entries: Vec<_> = flags
+        .iter()
+        .filter(|(path, flag)| **flag != CompareFlag::Inside && path.starts_with(dir) && path.as_path() != dir)
+        .map(|(path, flag)| (path.clone(), symlink_metadata(path).is_ok_and(|meta| meta.is_dir()), *flag))
+        .filter(|(_, is_dir, flag)| flag.selects(*is_dir))
+        .map(|(path, is_dir, _)| (path, is_dir))
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompareMethod {
+    /// Size and modification time.
+    Quick,
+    /// Size and file contents, times ignored for equality.
+    Content,
+}
+
+impl CompareMethod {
+    pub fn label(self) -> &'static str {
+        match self {
+            CompareMethod::Quick => "Size and time",
+            CompareMethod::Content => "Content",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct CompareOptions {
+    pub method: CompareMethod,
+    /// Directories on both sides are walked, flagging the entries inside them too.
+    pub recursive: bool,
+}
+
+/// Flags for both panels, keyed by the entry's path; with `recursive` also for entries below the top level.
+pub struct CompareResult {
+    pub left: HashMap<PathBuf, CompareFlag>,
+    pub right: HashMap<PathBuf, CompareFlag>,
+}
+
+enum CompareMessage {
+    Progress(usize),
+    Done(CompareResult),
+}
+
+/// A running comparison of two panels' top-level entries.
+pub struct DirCompare {
+    pub compared: usize,
+    pub result: Option<CompareResult>,
+    receiver: Receiver<CompareMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl DirCompare {
+    pub fn start(left: Vec<PathBuf>, right: Vec<PathBuf>, options: CompareOptions) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || run_compare(left, right, options, sender, thread_cancel));
+
+        Self { compared: 0, result: None, receiver, cancel }
+    }
+
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                CompareMessage::Progress(compared) => self.compared = compared,
+                CompareMessage::Done(result) => self.result = Some(result),
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for DirCompare {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+struct Walker<'a> {
+    options: CompareOptions,
+    sender: &'a Sender<CompareMessage>,
+    cancel: &'a AtomicBool,
+    compared: usize,
+    result: CompareResult,
+}
+
+fn run_compare(left: Vec<PathBuf>, right: Vec<PathBuf>, options: CompareOptions, sender: Sender<CompareMessage>, cancel: Arc<AtomicBool>) {
+    let mut walker = Walker { options, sender: &sender, cancel: &cancel, compared: 0, result: CompareResult { left: HashMap::new(), right: HashMap::new() } };
+    let by_name = |paths: Vec<PathBuf>| -> HashMap<OsString, PathBuf> { paths.into_iter().filter_map(|path| Some((path.file_name()?.to_os_string(), path))).collect() };
+    walker.flag_entries(by_name(left), by_name(right));
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let _ = sender.send(CompareMessage::Done(walker.result));
+}
+
+impl Walker<'_> {
+    /// Flags the differences between two sets of entries matched by name; true if there are any.
+    fn flag_entries(&mut self, left: HashMap<OsString, PathBuf>, right: HashMap<OsString, PathBuf>) -> bool {
+        let mut differs = false;
+        for (name, left_path) in &left {
+            if self.cancel.load(Ordering::Relaxed) {
+                return differs;
+            }
+            match right.get(name) {
+                Some(right_path) => {
+                    let (left_flag, right_flag) = self.compare_pair(left_path, right_path);
+                    if let Some(flag) = left_flag {
+                        self.result.left.insert(left_path.clone(), flag);
+                    }
+                    if let Some(flag) = right_flag {
+                        self.result.right.insert(right_path.clone(), flag);
+                    }
+                    differs |= left_flag.is_some() || right_flag.is_some();
+                }
+                None => {
+                    self.result.left.insert(left_path.clone(), CompareFlag::OnlyHere);
+                    differs = true;
+                }
+            }
+        }
+        for (_, right_path) in right.into_iter().filter(|(name, _)| !left.contains_key(name)) {
+            self.result.right.insert(right_path, CompareFlag::OnlyHere);
+            differs = true;
+        }
+        differs
+    }
+
+    /// Flags for an entry present on both sides; `None` where nothing stands out.
+    fn compare_pair(&mut self, left: &Path, right: &Path) -> (Option<CompareFlag>, Option<CompareFlag>) {
+        self.compared += 1;
+        if self.compared.is_multiple_of(PROGRESS_INTERVAL) {
+            let _ = self.sender.send(CompareMessage::Progress(self.compared));
+        }
+        let (Ok(left_meta), Ok(right_meta)) = (symlink_metadata(left), symlink_metadata(right)) else {
+            return (Some(CompareFlag::Different), Some(CompareFlag::Different));
+        };
+
+        match (left_meta.is_dir(), right_meta.is_dir()) {
+            (true, true) if self.options.recursive && self.flag_subtree(left, right) => (Some(CompareFlag::Inside), Some(CompareFlag::Inside)),
+            (true, true) => (None, None),
+            (false, false) => self.compare_files(left, &left_meta, right, &right_meta),
+            _ => (Some(CompareFlag::Different), Some(CompareFlag::Different)),
+        }
+    }
+
+    fn compare_files(&self, left: &Path, left_meta: &Metadata, right: &Path, right_meta: &Metadata) -> (Option<CompareFlag>, Option<CompareFlag>) {
+        if files_match(left, left_meta, right, right_meta, self.options.method) {
+            return (None, None);
+        }
+
+        let left_time = left_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let right_time = right_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let larger_if = |bigger: bool| bigger.then_some(CompareFlag::Larger);
+        if is_close(left_time, right_time) {
+            (Some(CompareFlag::Different), Some(CompareFlag::Different))
+        } else if left_time > right_time {
+            (Some(CompareFlag::Newer), larger_if(right_meta.len() > left_meta.len()))
+        } else {
+            (larger_if(left_meta.len() > right_meta.len()), Some(CompareFlag::Newer))
+        }
+    }
+
+    /// Flags every entry that differs below two directories, so the differences can be copied one by one.
+    fn flag_subtree(&mut self, left: &Path, right: &Path) -> bool {
+        let entries = |dir: &Path| -> HashMap<OsString, PathBuf> { read_dir(dir).map(|entries| entries.flatten().map(|entry| (entry.file_name(), entry.path())).collect()).unwrap_or_default() };
+        self.flag_entries(entries(left), entries(right))
+    }
+}
+
+/// Whether two regular files count as the same under `method`.
+pub fn files_match(left: &Path, left_meta: &Metadata, right: &Path, right_meta: &Metadata, method: CompareMethod) -> bool {
+    left_meta.len() == right_meta.len()
+        && match method {
+            CompareMethod::Quick => is_close(left_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), right_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)),
+            CompareMethod::Content => same_content(left, right).unwrap_or(false),
+        }
+}
+
+/// Equal within the FAT time resolution.
+pub fn is_close(a: SystemTime, b: SystemTime) -> bool {
+    let difference = a.duration_since(b).or_else(|_| b.duration_since(a)).unwrap_or_default();
+    difference <= TIME_TOLERANCE
+}
+
+/// Byte-by-byte comparison; callers have already checked the sizes match.
+fn same_content(left: &Path, right: &Path) -> Result<bool, Error> {
+    let (mut left_file, mut right_file) = (open_regular_file(left)?, open_regular_file(right)?);
+    let (mut left_buffer, mut right_buffer) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let read = left_file.read(&mut left_buffer)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        right_file.read_exact(&mut right_buffer[..read])?;
+        if left_buffer[..read] != right_buffer[..read] {
+            return Ok(false);
+        }
+    }
+}
@@ -14,6 +14,7 @@ pub const COLOR_DIRECTORY: Color = Color::Rgb(255, 0, 255);                  //
 pub const COLOR_DIRECTORY_DARK: Color = Color::Rgb(150, 0, 150);             // Dark magenta
 pub const COLOR_DIRECTORY_FIX: Color = Color::Rgb(255, 0, 255);              // Magenta
 pub const COLOR_FILE: Color = Color::Rgb(114, 137, 218);                     // Soft purple/blue
+pub const COLOR_SPECIAL: Color = Color::Rgb(255, 140, 0);                    // Orange for FIFOs, sockets and devices
 pub const COLOR_RENAME_BACKGROUND: Color = Color::Rgb(255, 0, 128);          // Hot pink
 pub const COLOR_SELECTED_BACKGROUND: Color = Color::Rgb(148, 0, 211);        // Purple
 pub const COLOR_SELECTED_BACKGROUND_INACTIVE: Color = Color::Rgb(45, 0, 75); // Dark purple
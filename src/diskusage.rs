@@ -0,0 +1,323 @@
+use crate::fs_ops::delete_path;
+use crate::utils::display_name;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{Metadata, read_dir, symlink_metadata};
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Finished scans kept for reopening the view inside them.
+pub const CACHE_SIZE: usize = 4;
+const MAX_WORKERS: usize = 8;
+
+/// Sizes of a file or a whole subtree.
+#[derive(Clone, Copy, Default)]
+pub struct Usage {
+    /// Sum of file lengths.
+    pub apparent: u64,
+    /// Allocated blocks; smaller for sparse files, larger for many tiny ones.
+    pub disk: u64,
+    pub files: u64,
+}
+
+impl Usage {
+    fn of(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let disk = std::os::unix::fs::MetadataExt::blocks(metadata) * 512;
+        #[cfg(not(unix))]
+        let disk = metadata.len();
+        Self { apparent: metadata.len(), disk, files: 1 }
+    }
+
+    fn add(&mut self, other: Usage) {
+        self.apparent += other.apparent;
+        self.disk += other.disk;
+        self.files += other.files;
+    }
+
+    fn subtract(&mut self, other: Usage) {
+        self.apparent = self.apparent.saturating_sub(other.apparent);
+        self.disk = self.disk.saturating_sub(other.disk);
+        self.files = self.files.saturating_sub(other.files);
+    }
+
+    pub fn size(&self, apparent: bool) -> u64 {
+        if apparent { self.apparent } else { self.disk }
+    }
+}
+
+struct DirUsage {
+    /// Files directly inside.
+    own: Usage,
+    /// The same files one by one, so listing a directory needs no disk access.
+    files: Vec<(OsString, Usage)>,
+    subdirs: Vec<PathBuf>,
+    /// Own plus every subdirectory, filled in once the scan is complete.
+    total: Usage,
+}
+
+/// Finished scan of a subtree: one entry per directory, holding its files.
+pub struct UsageTree {
+    pub root: PathBuf,
+    pub scanned_at: Instant,
+    /// Directories that could not be read.
+    pub unreadable: usize,
+    dirs: HashMap<PathBuf, DirUsage>,
+}
+
+/// A child of the directory being browsed.
+pub struct UsageRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+    pub usage: Usage,
+}
+
+impl UsageTree {
+    pub fn contains(&self, dir: &Path) -> bool {
+        self.dirs.contains_key(dir)
+    }
+
+    pub fn total(&self, dir: &Path) -> Usage {
+        self.dirs.get(dir).map(|usage| usage.total).unwrap_or_default()
+    }
+
+    /// Subdirectories and files of `dir` as scanned, biggest first.
+    pub fn rows(&self, dir: &Path, apparent: bool) -> Vec<UsageRow> {
+        let Some(usage) = self.dirs.get(dir) else { return Vec::new() };
+        let name_of = |path: &Path| path.file_name().map(display_name).unwrap_or_default();
+        let mut rows: Vec<UsageRow> = usage.subdirs.iter().map(|path| UsageRow { path: path.clone(), name: name_of(path), is_dir: true, usage: self.total(path) }).collect();
+        rows.extend(usage.files.iter().map(|(name, file_usage)| UsageRow { path: dir.join(name), name: display_name(name), is_dir: false, usage: *file_usage }));
+        rows.sort_by(|a, b| b.usage.size(apparent).cmp(&a.usage.size(apparent)).then_with(|| a.name.cmp(&b.name)));
+        rows
+    }
+
+    /// Drops a deleted entry (and any directories below it) and takes its usage off every ancestor.
+    pub fn remove(&mut self, path: &Path, usage: Usage) {
+        let mut stack = vec![path.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            if let Some(removed) = self.dirs.remove(&dir) {
+                stack.extend(removed.subdirs);
+            }
+        }
+        if let Some(parent) = path.parent().and_then(|parent| self.dirs.get_mut(parent)) {
+            parent.subdirs.retain(|subdir| subdir != path);
+            parent.files.retain(|(name, _)| Some(name.as_os_str()) != path.file_name());
+        }
+        for ancestor in path.ancestors().skip(1) {
+            match self.dirs.get_mut(ancestor) {
+                Some(ancestor_usage) => ancestor_usage.total.subtract(usage),
+                None => break,
+            }
+        }
+    }
+}
+
+enum ScanMessage {
+    Dir(PathBuf, Usage, Vec<(OsString, Usage)>, Vec<PathBuf>),
+    Unreadable,
+}
+
+/// A parallel scan in progress: workers share a stack of directories still to read.
+pub struct UsageScan {
+    pub root: PathBuf,
+    pub dirs_scanned: usize,
+    pub bytes_scanned: u64,
+    dirs: HashMap<PathBuf, DirUsage>,
+    unreadable: usize,
+    receiver: Receiver<ScanMessage>,
+    pending: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl UsageScan {
+    pub fn start(root: PathBuf) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        // Directories queued or being read; the scan is over when it drops to zero
+        let pending = Arc::new(AtomicUsize::new(1));
+        let queue = Arc::new(Mutex::new(vec![root.clone()]));
+        let device = device_of(&root);
+        let workers = thread::available_parallelism().map_or(2, |count| count.get()).min(MAX_WORKERS);
+        for _ in 0..workers {
+            let (sender, queue, pending, cancel) = (sender.clone(), Arc::clone(&queue), Arc::clone(&pending), Arc::clone(&cancel));
+            thread::spawn(move || scan_worker(&queue, &pending, &cancel, &sender, device));
+        }
+
+        Self { root, dirs_scanned: 0, bytes_scanned: 0, dirs: HashMap::new(), unreadable: 0, receiver, pending, cancel }
+    }
+
+    /// Collects worker results; returns the tree once every directory has been read.
+    pub fn poll(&mut self) -> Option<UsageTree> {
+        self.drain();
+        if self.pending.load(Ordering::Acquire) != 0 {
+            return None;
+        }
+        // Workers send before decrementing, so whatever arrived since the drain is all there is
+        self.drain();
+
+        let mut dirs = std::mem::take(&mut self.dirs);
+        // Deepest first, so every subdirectory total is final before its parent adds it
+        let mut order: Vec<PathBuf> = dirs.keys().cloned().collect();
+        order.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+        for dir in order {
+            let Some(usage) = dirs.get_mut(&dir) else { continue };
+            usage.total.add(usage.own);
+            let total = usage.total;
+            if dir != self.root && let Some(parent) = dir.parent().and_then(|parent| dirs.get_mut(parent)) {
+                parent.total.add(total);
+            }
+        }
+        Some(UsageTree { root: self.root.clone(), scanned_at: Instant::now(), unreadable: self.unreadable, dirs })
+    }
+
+    fn drain(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                ScanMessage::Dir(dir, own, files, subdirs) => {
+                    self.dirs_scanned += 1;
+                    self.bytes_scanned += own.disk;
+                    self.dirs.insert(dir, DirUsage { own, files, subdirs, total: Usage::default() });
+                }
+                ScanMessage::Unreadable => self.unreadable += 1,
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for UsageScan {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn scan_worker(queue: &Mutex<Vec<PathBuf>>, pending: &AtomicUsize, cancel: &AtomicBool, sender: &Sender<ScanMessage>, device: Option<u64>) {
+    while !cancel.load(Ordering::Relaxed) {
+        let next = queue.lock().map(|mut queue| queue.pop()).unwrap_or(None);
+        let Some(dir) = next else {
+            if pending.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+            continue;
+        };
+
+        let Ok(entries) = read_dir(&dir) else {
+            let _ = sender.send(ScanMessage::Unreadable);
+            // Still listed by its parent, with nothing inside
+            let _ = sender.send(ScanMessage::Dir(dir, Usage::default(), Vec::new(), Vec::new()));
+            pending.fetch_sub(1, Ordering::AcqRel);
+            continue;
+        };
+        let mut own = Usage::default();
+        let mut files = Vec::new();
+        let mut subdirs = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = symlink_metadata(&path) else { continue };
+            // Other filesystems mounted below the root are not counted, like `du -x`
+            if metadata.is_dir() && (device.is_none() || device_of_metadata(&metadata) == device) {
+                subdirs.push(path);
+            } else if !metadata.is_dir() {
+                let usage = Usage::of(&metadata);
+                own.add(usage);
+                files.push((entry.file_name(), usage));
+            }
+        }
+        pending.fetch_add(subdirs.len(), Ordering::AcqRel);
+        if let Ok(mut queue) = queue.lock() {
+            queue.extend(subdirs.iter().cloned());
+        }
+        let _ = sender.send(ScanMessage::Dir(dir, own, files, subdirs));
+        pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn device_of(path: &Path) -> Option<u64> {
+    symlink_metadata(path).ok().and_then(|metadata| device_of_metadata(&metadata))
+}
+
+#[cfg(unix)]
+fn device_of_metadata(metadata: &Metadata) -> Option<u64> {
+    Some(std::os::unix::fs::MetadataExt::dev(metadata))
+}
+
+#[cfg(not(unix))]
+fn device_of_metadata(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Browsing state of the disk usage view over a finished scan.
+pub struct UsageView {
+    pub tree: UsageTree,
+    pub dir: PathBuf,
+    pub rows: Vec<UsageRow>,
+    pub selected: usize,
+    /// Sort and bar by apparent size instead of allocated size.
+    pub is_apparent: bool,
+    pub is_delete_prompt: bool,
+}
+
+impl UsageView {
+    pub fn new(tree: UsageTree, dir: PathBuf) -> Self {
+        let rows = tree.rows(&dir, false);
+        Self { tree, dir, rows, selected: 0, is_apparent: false, is_delete_prompt: false }
+    }
+
+    pub fn selected_row(&self) -> Option<&UsageRow> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn select_by(&mut self, delta: isize) {
+        let max = self.rows.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, max) as usize;
+    }
+
+    fn show(&mut self, dir: PathBuf, select: Option<&Path>) {
+        self.rows = self.tree.rows(&dir, self.is_apparent);
+        self.selected = select.and_then(|path| self.rows.iter().position(|row| row.path == path)).unwrap_or(0);
+        self.dir = dir;
+    }
+
+    /// Drills into the selected directory.
+    pub fn enter(&mut self) {
+        if let Some(row) = self.selected_row().filter(|row| row.is_dir) {
+            self.show(row.path.clone(), None);
+        }
+    }
+
+    /// Back to the parent, as long as it is part of the scan.
+    pub fn leave(&mut self) {
+        let Some(parent) = self.dir.parent().filter(|parent| self.tree.contains(parent)).map(Path::to_path_buf) else { return };
+        let child = self.dir.clone();
+        self.show(parent, Some(&child));
+    }
+
+    pub fn toggle_apparent(&mut self) {
+        self.is_apparent = !self.is_apparent;
+        let selected = self.selected_row().map(|row| row.path.clone());
+        self.show(self.dir.clone(), selected.as_deref());
+    }
+
+    /// Deletes the selected entry from disk and from the scan.
+    pub fn delete_selected(&mut self) -> Result<(), Error> {
+        self.is_delete_prompt = false;
+        let Some(row) = self.selected_row() else { return Ok(()) };
+        let (path, usage) = (row.path.clone(), row.usage);
+        delete_path(path.clone(), row.is_dir)?;
+        self.tree.remove(&path, usage);
+        let selected = self.selected;
+        self.show(self.dir.clone(), None);
+        self.selected = selected.min(self.rows.len().saturating_sub(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,590 @@
+use crate::app::TextInput;
+use crate::archive::{for_each_entry, is_archive};
+use crate::fs_ops::open_regular_file;
+use crate::gitignore::{ancestor_gitignores, is_ignored, with_dir_rules};
+use crate::lineindex::{MAX_LINE_BYTES, read_line_capped};
+use crate::utils::{parse_size, wildcard_match};
+use crate::viewer::is_binary_content;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, read_dir};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Characters kept on each side of a content match in result snippets.
+const SNIPPET_CONTEXT: usize = 32;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FindKind {
+    Any,
+    Files,
+    Dirs,
+}
+
+impl FindKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FindKind::Any => "Files and directories",
+            FindKind::Files => "Files only",
+            FindKind::Dirs => "Directories only",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            FindKind::Any => FindKind::Files,
+            FindKind::Files => FindKind::Dirs,
+            FindKind::Dirs => FindKind::Any,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FindField {
+    StartDir,
+    Name,
+    Regex,
+    Exclude,
+    Kind,
+    MinSize,
+    MaxSize,
+    NewerDays,
+    OlderDays,
+    MaxDepth,
+    FollowSymlinks,
+    Archives,
+    Gitignore,
+    Content,
+    ContentRegex,
+    CaseSensitive,
+    WholeWord,
+}
+
+impl FindField {
+    pub const ALL: [FindField; 17] = [
+        FindField::StartDir,
+        FindField::Name,
+        FindField::Regex,
+        FindField::Exclude,
+        FindField::Kind,
+        FindField::MinSize,
+        FindField::MaxSize,
+        FindField::NewerDays,
+        FindField::OlderDays,
+        FindField::MaxDepth,
+        FindField::FollowSymlinks,
+        FindField::Archives,
+        FindField::Gitignore,
+        FindField::Content,
+        FindField::ContentRegex,
+        FindField::CaseSensitive,
+        FindField::WholeWord,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FindField::StartDir => "Start directory",
+            FindField::Name => "File name",
+            FindField::Regex => "Name is regex",
+            FindField::Exclude => "Exclude",
+            FindField::Kind => "Type",
+            FindField::MinSize => "Min size",
+            FindField::MaxSize => "Max size",
+            FindField::NewerDays => "Modified within days",
+            FindField::OlderDays => "Modified before days",
+            FindField::MaxDepth => "Max depth",
+            FindField::FollowSymlinks => "Follow symlinks",
+            FindField::Archives => "Search in archives",
+            FindField::Gitignore => "Respect .gitignore",
+            FindField::Content => "Containing text",
+            FindField::ContentRegex => "Text is regex",
+            FindField::CaseSensitive => "Case sensitive",
+            FindField::WholeWord => "Whole words",
+        }
+    }
+
+    /// Toggle fields are changed with Space/Left/Right instead of typing.
+    pub fn is_toggle(self) -> bool {
+        matches!(
+            self,
+            FindField::Regex | FindField::Kind | FindField::FollowSymlinks | FindField::Archives | FindField::Gitignore | FindField::ContentRegex | FindField::CaseSensitive | FindField::WholeWord
+        )
+    }
+}
+
+/// Find dialog (Alt+F7). Empty text fields mean "no limit"; a non-empty
+/// "Containing text" turns the file search into a content search.
+pub struct FindDialog {
+    pub start_dir: TextInput,
+    pub name: TextInput,
+    pub use_regex: bool,
+    pub exclude: TextInput,
+    pub kind: FindKind,
+    pub min_size: TextInput,
+    pub max_size: TextInput,
+    pub newer_days: TextInput,
+    pub older_days: TextInput,
+    pub max_depth: TextInput,
+    pub follow_symlinks: bool,
+    pub search_archives: bool,
+    pub respect_gitignore: bool,
+    pub content: TextInput,
+    pub content_regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub focus: usize,
+}
+
+impl FindDialog {
+    pub fn new() -> Self {
+        Self {
+            start_dir: TextInput::new(),
+            name: TextInput::new(),
+            use_regex: false,
+            exclude: TextInput::new(),
+            kind: FindKind::Any,
+            min_size: TextInput::new(),
+            max_size: TextInput::new(),
+            newer_days: TextInput::new(),
+            older_days: TextInput::new(),
+            max_depth: TextInput::new(),
+            follow_symlinks: false,
+            search_archives: false,
+            respect_gitignore: false,
+            content: TextInput::new(),
+            content_regex: false,
+            case_sensitive: false,
+            whole_word: false,
+            focus: 1,
+        }
+    }
+
+    pub fn focused(&self) -> FindField {
+        FindField::ALL[self.focus]
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FindField::ALL.len();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FindField::ALL.len() - 1) % FindField::ALL.len();
+    }
+
+    /// Text input behind a field, None for toggle fields.
+    pub fn input(&self, field: FindField) -> Option<&TextInput> {
+        match field {
+            FindField::StartDir => Some(&self.start_dir),
+            FindField::Name => Some(&self.name),
+            FindField::Exclude => Some(&self.exclude),
+            FindField::MinSize => Some(&self.min_size),
+            FindField::MaxSize => Some(&self.max_size),
+            FindField::NewerDays => Some(&self.newer_days),
+            FindField::OlderDays => Some(&self.older_days),
+            FindField::MaxDepth => Some(&self.max_depth),
+            FindField::Content => Some(&self.content),
+            _ => None,
+        }
+    }
+
+    pub fn focused_input_mut(&mut self) -> Option<&mut TextInput> {
+        match self.focused() {
+            FindField::StartDir => Some(&mut self.start_dir),
+            FindField::Name => Some(&mut self.name),
+            FindField::Exclude => Some(&mut self.exclude),
+            FindField::MinSize => Some(&mut self.min_size),
+            FindField::MaxSize => Some(&mut self.max_size),
+            FindField::NewerDays => Some(&mut self.newer_days),
+            FindField::OlderDays => Some(&mut self.older_days),
+            FindField::MaxDepth => Some(&mut self.max_depth),
+            FindField::Content => Some(&mut self.content),
+            _ => None,
+        }
+    }
+
+    pub fn toggle_focused(&mut self) {
+        match self.focused() {
+            FindField::Regex => self.use_regex = !self.use_regex,
+            FindField::Kind => self.kind = self.kind.next(),
+            FindField::FollowSymlinks => self.follow_symlinks = !self.follow_symlinks,
+            FindField::Archives => self.search_archives = !self.search_archives,
+            FindField::Gitignore => self.respect_gitignore = !self.respect_gitignore,
+            FindField::ContentRegex => self.content_regex = !self.content_regex,
+            FindField::CaseSensitive => self.case_sensitive = !self.case_sensitive,
+            FindField::WholeWord => self.whole_word = !self.whole_word,
+            _ => {}
+        }
+    }
+
+    /// Display value for toggle fields.
+    pub fn toggle_value(&self, field: FindField) -> &'static str {
+        let check = |on: bool| if on { "[x]" } else { "[ ]" };
+        match field {
+            FindField::Regex => check(self.use_regex),
+            FindField::Kind => self.kind.label(),
+            FindField::FollowSymlinks => check(self.follow_symlinks),
+            FindField::Archives => check(self.search_archives),
+            FindField::Gitignore => check(self.respect_gitignore),
+            FindField::ContentRegex => check(self.content_regex),
+            FindField::CaseSensitive => check(self.case_sensitive),
+            FindField::WholeWord => check(self.whole_word),
+            _ => "",
+        }
+    }
+
+    /// Validates the form into search criteria.
+    pub fn criteria(&self) -> Result<FindCriteria, String> {
+        let start_dir = PathBuf::from(self.start_dir.text.trim());
+        if !start_dir.is_dir() {
+            return Err(format!("Not a directory: {}", start_dir.display()));
+        }
+
+        let pattern = self.name.text.trim();
+        let name = if pattern.is_empty() {
+            NameMatcher::Any
+        } else if self.use_regex {
+            let regex = RegexBuilder::new(pattern).case_insensitive(true).build().map_err(|e| format!("Invalid regex: {}", e))?;
+            NameMatcher::Regex(regex)
+        } else {
+            NameMatcher::Glob(split_globs(pattern))
+        };
+
+        let content = match self.content.text.as_str() {
+            "" => None,
+            text => {
+                let pattern = if self.content_regex { text.to_string() } else { regex::escape(text) };
+                let pattern = if self.whole_word { format!(r"\b(?:{})\b", pattern) } else { pattern };
+                let regex = RegexBuilder::new(&pattern).case_insensitive(!self.case_sensitive).build().map_err(|e| format!("Invalid regex: {}", e))?;
+                Some(regex)
+            }
+        };
+
+        let size = |input: &TextInput, label: &str| -> Result<Option<u64>, String> {
+            let text = input.text.trim();
+            if text.is_empty() { Ok(None) } else { parse_size(text).map(Some).ok_or(format!("Invalid {}: {}", label, text)) }
+        };
+        let number = |input: &TextInput, label: &str| -> Result<Option<u64>, String> {
+            let text = input.text.trim();
+            if text.is_empty() { Ok(None) } else { text.parse().map(Some).map_err(|_| format!("Invalid {}: {}", label, text)) }
+        };
+        let days_ago = |days: u64| SystemTime::now().checked_sub(Duration::from_secs(days * 86400)).unwrap_or(SystemTime::UNIX_EPOCH);
+
+        Ok(FindCriteria {
+            start_dir,
+            name,
+            exclude: self.exclude.text.split([';', ',']).map(str::trim).filter(|g| !g.is_empty()).map(String::from).collect(),
+            // Only files have content to search
+            kind: if content.is_some() { FindKind::Files } else { self.kind },
+            min_size: size(&self.min_size, "min size")?,
+            max_size: size(&self.max_size, "max size")?,
+            newer_than: number(&self.newer_days, "days")?.map(days_ago),
+            older_than: number(&self.older_days, "days")?.map(days_ago),
+            max_depth: number(&self.max_depth, "depth")?.map(|d| d as usize),
+            follow_symlinks: self.follow_symlinks,
+            search_archives: self.search_archives,
+            respect_gitignore: self.respect_gitignore,
+            content,
+        })
+    }
+}
+
+/// Splits "*.rs;*.toml" into globs; bare words match anywhere in the name.
+fn split_globs(text: &str) -> Vec<String> {
+    text.split([';', ','])
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+        .map(|g| if g.contains(['*', '?']) { g.to_string() } else { format!("*{}*", g) })
+        .collect()
+}
+
+pub enum NameMatcher {
+    Any,
+    Glob(Vec<String>),
+    Regex(Regex),
+}
+
+impl NameMatcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Any => true,
+            NameMatcher::Glob(patterns) => patterns.iter().any(|p| wildcard_match(p, name)),
+            NameMatcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+pub struct FindCriteria {
+    pub start_dir: PathBuf,
+    pub name: NameMatcher,
+    pub exclude: Vec<String>,
+    pub kind: FindKind,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub newer_than: Option<SystemTime>,
+    pub older_than: Option<SystemTime>,
+    /// Directory levels to descend below `start_dir`; 0 searches the start directory only.
+    pub max_depth: Option<usize>,
+    pub follow_symlinks: bool,
+    /// Zip and tar members count as entries below their archive.
+    pub search_archives: bool,
+    pub respect_gitignore: bool,
+    pub content: Option<Regex>,
+}
+
+impl FindCriteria {
+    fn matches(&self, name: &str, metadata: &fs::Metadata) -> bool {
+        self.matches_entry(name, metadata.is_dir(), metadata.is_file(), metadata.len(), metadata.modified().ok())
+    }
+
+    fn matches_entry(&self, name: &str, is_dir: bool, is_file: bool, len: u64, modified: Option<SystemTime>) -> bool {
+        match self.kind {
+            FindKind::Files if !is_file => return false,
+            FindKind::Dirs if !is_dir => return false,
+            _ => {}
+        }
+        if !self.name.matches(name) {
+            return false;
+        }
+        // Size limits only make sense for files
+        if self.min_size.is_some() || self.max_size.is_some() {
+            if is_dir {
+                return false;
+            }
+            if self.min_size.is_some_and(|min| len < min) || self.max_size.is_some_and(|max| len > max) {
+                return false;
+            }
+        }
+        if self.newer_than.is_some() || self.older_than.is_some() {
+            let Some(modified) = modified else { return false };
+            if self.newer_than.is_some_and(|t| modified < t) || self.older_than.is_some_and(|t| modified > t) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_excluded(&self, name: &str) -> bool {
+        self.exclude.iter().any(|p| wildcard_match(p, name))
+    }
+}
+
+/// One search hit. Content matches carry the line (0-based) and the matched byte range.
+pub struct FindResult {
+    pub path: PathBuf,
+    /// Member path when the hit is inside the archive at `path`.
+    pub archive_entry: Option<String>,
+    pub line: Option<usize>,
+    /// The matched line, cut around the match when long.
+    pub snippet: String,
+    /// Byte range of the match within `snippet`.
+    pub snippet_range: Range<usize>,
+    /// Byte range of the match within the whole line.
+    pub match_range: Range<usize>,
+}
+
+pub enum FindMessage {
+    Found(FindResult),
+    Progress(usize),
+    Done,
+}
+
+/// A running (or finished) background search and its collected results.
+pub struct FindSearch {
+    pub start_dir: PathBuf,
+    pub results: Vec<FindResult>,
+    pub selected: usize,
+    pub scanned: usize,
+    pub done: bool,
+    receiver: Receiver<FindMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl FindSearch {
+    pub fn start(criteria: FindCriteria) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let start_dir = criteria.start_dir.clone();
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || run_search(criteria, sender, thread_cancel));
+
+        Self { start_dir, results: Vec::new(), selected: 0, scanned: 0, done: false, receiver, cancel }
+    }
+
+    /// Drains messages from the search thread without blocking.
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                FindMessage::Found(result) => self.results.push(result),
+                FindMessage::Progress(scanned) => self.scanned = scanned,
+                FindMessage::Done => self.done = true,
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn selected_result(&self) -> Option<&FindResult> {
+        self.results.get(self.selected)
+    }
+
+    pub fn select_by(&mut self, delta: isize) {
+        let max = self.results.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, max) as usize;
+    }
+}
+
+impl Drop for FindSearch {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Sends every line of `reader` matching `regex` as a hit in `path`, or in its `archive_entry`; returns false once the receiver is gone.
+fn search_content(reader: impl Read, path: &Path, archive_entry: Option<&str>, regex: &Regex, sender: &Sender<FindMessage>, cancel: &AtomicBool) -> bool {
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf().map_or(true, is_binary_content) {
+        return true;
+    }
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    // Only the head of very long lines is searched, as much as the viewer shows
+    while let Ok(has_newline) = read_line_capped(&mut reader, &mut buffer, MAX_LINE_BYTES) {
+        if !has_newline && buffer.is_empty() {
+            break;
+        }
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end_matches('\r');
+        if let Some(found) = regex.find(line) {
+            let (snippet, snippet_range) = snippet_around(line, found.range());
+            let result = FindResult { path: path.to_path_buf(), archive_entry: archive_entry.map(String::from), line: Some(line_number), snippet, snippet_range, match_range: found.range() };
+            if sender.send(FindMessage::Found(result)).is_err() {
+                return false;
+            }
+        }
+        if !has_newline {
+            break;
+        }
+        buffer.clear();
+        line_number += 1;
+    }
+    true
+}
+
+/// The part of `line` around the match at `range`, with `…` where it was cut, and the match's range within it.
+/// Long matches are cut too, keeping their start.
+fn snippet_around(line: &str, range: Range<usize>) -> (String, Range<usize>) {
+    let start = line[..range.start].char_indices().rev().nth(SNIPPET_CONTEXT - 1).map_or(0, |(index, _)| index);
+    let match_end = line[range.clone()].char_indices().nth(SNIPPET_CONTEXT * 2).map_or(range.end, |(index, _)| range.start + index);
+    let end = if match_end < range.end { match_end } else { line[range.end..].char_indices().nth(SNIPPET_CONTEXT).map_or(line.len(), |(index, _)| range.end + index) };
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < line.len() { "…" } else { "" };
+    let snippet = format!("{}{}{}", prefix, &line[start..end], suffix);
+    let offset = prefix.len() + range.start - start;
+    (snippet, offset..offset + match_end - range.start)
+}
+
+/// Matches the members of the archive at `path` like entries of a directory `depth` levels down;
+/// returns false once the receiver is gone or the search is cancelled.
+fn search_archive(criteria: &FindCriteria, path: &Path, depth: usize, sender: &Sender<FindMessage>, cancel: &AtomicBool) -> bool {
+    let mut delivered = true;
+    // A corrupt archive is searched as far as it can be read
+    let _ = for_each_entry(path, |entry, reader| {
+        if cancel.load(Ordering::Relaxed) {
+            delivered = false;
+            return false;
+        }
+        let components: Vec<&str> = entry.path.split('/').collect();
+        let entry_depth = depth + components.len() - 1;
+        if criteria.max_depth.is_some_and(|max| entry_depth > max) || components.iter().any(|component| criteria.is_excluded(component)) {
+            return true;
+        }
+        if !criteria.matches_entry(entry.name(), entry.is_dir, !entry.is_dir, entry.size, entry.modified) {
+            return true;
+        }
+        delivered = match &criteria.content {
+            Some(regex) => search_content(reader, path, Some(&entry.path), regex, sender, cancel),
+            None => sender.send(FindMessage::Found(FindResult { path: path.to_path_buf(), archive_entry: Some(entry.path.clone()), line: None, snippet: String::new(), snippet_range: 0..0, match_range: 0..0 })).is_ok(),
+        };
+        delivered
+    });
+    delivered
+}
+
+/// Breadth-first walk so shallow matches show up first.
+fn run_search(criteria: FindCriteria, sender: Sender<FindMessage>, cancel: Arc<AtomicBool>) {
+    let root_rules = if criteria.respect_gitignore { ancestor_gitignores(&criteria.start_dir) } else { Vec::new() };
+    let mut queue = VecDeque::from([(criteria.start_dir.clone(), 0usize, Arc::new(root_rules))]);
+    let mut visited = HashSet::new();
+    let mut scanned = 0usize;
+
+    if let Ok(canonical) = criteria.start_dir.canonicalize() {
+        visited.insert(canonical);
+    }
+
+    while let Some((dir, depth, inherited_rules)) = queue.pop_front() {
+        let Ok(entries) = read_dir(&dir) else { continue };
+
+        let rules = if criteria.respect_gitignore { with_dir_rules(&dir, inherited_rules) } else { inherited_rules };
+
+        for entry in entries.flatten() {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            scanned += 1;
+            if scanned.is_multiple_of(256) && sender.send(FindMessage::Progress(scanned)).is_err() {
+                return;
+            }
+
+            let path = entry.path();
+            let metadata = if criteria.follow_symlinks { fs::metadata(&path) } else { fs::symlink_metadata(&path) };
+            let Ok(metadata) = metadata else { continue };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = metadata.is_dir();
+
+            if criteria.is_excluded(&name) || (criteria.respect_gitignore && (name == ".git" || is_ignored(&rules, &path, is_dir))) {
+                continue;
+            }
+
+            if criteria.matches(&name, &metadata) {
+                let delivered = match &criteria.content {
+                    Some(regex) => match open_regular_file(&path) {
+                        Ok(file) => search_content(file, &path, None, regex, &sender, &cancel),
+                        // Unreadable files just have no matches
+                        Err(_) => true,
+                    },
+                    None => sender.send(FindMessage::Found(FindResult { path: path.clone(), archive_entry: None, line: None, snippet: String::new(), snippet_range: 0..0, match_range: 0..0 })).is_ok(),
+                };
+                if !delivered {
+                    return;
+                }
+            }
+
+            let entry_depth = depth + 1;
+            if criteria.search_archives && metadata.is_file() && is_archive(&name) && criteria.max_depth.is_none_or(|max| entry_depth <= max) && !search_archive(&criteria, &path, entry_depth, &sender, &cancel) {
+                return;
+            }
+            if is_dir && criteria.max_depth.is_none_or(|max| entry_depth <= max) {
+                // Symlinked directories can form cycles
+                if criteria.follow_symlinks && let Ok(canonical) = path.canonicalize() && !visited.insert(canonical) {
+                    continue;
+                }
+                queue.push_back((path, entry_depth, Arc::clone(&rules)));
+            }
+        }
+    }
+
+    let _ = sender.send(FindMessage::Progress(scanned));
+    let _ = sender.send(FindMessage::Done);
+}
@@ -1,64 +1,391 @@
 use crate::app::Item;
-use crate::utils::format_size;
+use crate::utils::{display_name, wildcard_match};
 use chrono::Local;
+use std::collections::HashSet;
 use std::env;
-use std::fs::{self, File, create_dir, read_dir, remove_dir_all, remove_file, rename};
-use std::io::{self, Error};
+use std::fs::{self, DirEntry, File, create_dir, read_dir, remove_dir_all, remove_file, rename, symlink_metadata};
+use std::io::{self, Error, ErrorKind};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-pub fn load_directory_rows(path: &Path) -> Result<Vec<Item>, Error> {
-    let entries: Vec<_> = read_dir(path)?
-        .filter_map(|entry| entry.ok())
-        .collect();
+/// Per-panel listing filter: dotfile visibility plus a `;`-separated wildcard mask.
+/// Plain patterns (`*.rs;*.toml`) keep matching files, `!` patterns (`!target`) drop
+/// matching files and directories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelFilter {
+    pub show_hidden: bool,
+    pub mask: String,
+}
 
-    let has_parent = path.parent().is_some();
-    let mut children = Vec::with_capacity(entries.len() + usize::from(has_parent));
+impl PanelFilter {
+    pub fn new() -> Self {
+        Self { show_hidden: true, mask: String::new() }
+    }
 
-    // Don't add ".." on root folder.
-    if has_parent {
-        children.push(Item {
-            name_full: "..".to_string(),
-            name: "..".to_string(),
-            extension: String::new(),
-            is_dir: true,
-            size: String::new(),
-            size_bytes: 0,
-            modified: String::new(),
-        });
+    pub fn is_active(&self) -> bool {
+        !self.show_hidden || !self.mask.trim().is_empty()
     }
 
-    // Build Items with a single metadata() call per entry (one stat syscall)
-    for entry in &entries {
-        let entry_path = entry.path();
-        let metadata = entry.metadata().ok();
-        let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-        let name_full = entry_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
-        let name = if is_dir { name_full.clone() } else { entry_path.file_stem().and_then(|n| n.to_str()).unwrap_or("").to_string() };
-        let extension = if is_dir { String::new() } else { entry_path.extension().and_then(|e| e.to_str()).unwrap_or("").to_string() };
-        let size_bytes = if is_dir { 0 } else { metadata.as_ref().map(|m| m.len()).unwrap_or(0) };
-        let size = if is_dir { "<DIR>".to_string() } else { format_size(size_bytes) };
-        let modified = metadata.as_ref()
-            .and_then(|m| m.modified().ok())
-            .map(|t| {
-                let dt: chrono::DateTime<Local> = t.into();
-                dt.format("%d/%m/%y %H:%M").to_string()
-            })
-            .unwrap_or_default();
-
-        children.push(Item {
-            name_full,
-            name,
-            extension,
-            is_dir,
-            size,
-            size_bytes,
-            modified,
+    /// Short description for the path bar, e.g. "*.rs;*.toml, no hidden".
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.mask.trim().is_empty() {
+            parts.push(self.mask.trim().to_string());
+        }
+        if !self.show_hidden {
+            parts.push("no hidden".to_string());
+        }
+        parts.join(", ")
+    }
+
+    pub fn matches(&self, name: &str, is_dir: bool) -> bool {
+        if !self.show_hidden && name.starts_with('.') {
+            return false;
+        }
+
+        let patterns: Vec<&str> = self.mask.split([';', ',']).map(str::trim).filter(|p| !p.is_empty()).collect();
+        if patterns.iter().any(|p| p.strip_prefix('!').is_some_and(|neg| wildcard_match(neg, name))) {
+            return false;
+        }
+
+        // Directories stay visible so the panel remains navigable
+        let includes: Vec<&str> = patterns.into_iter().filter(|p| !p.starts_with('!')).collect();
+        is_dir || includes.is_empty() || includes.iter().any(|p| wildcard_match(p, name))
+    }
+}
+
+/// What an entry is, from its own (not followed) metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FileKind {
+    #[default]
+    File,
+    Dir,
+    Symlink,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+}
+
+impl FileKind {
+    #[cfg(unix)]
+    fn of(file_type: fs::FileType) -> Self {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_symlink() {
+            FileKind::Symlink
+        } else if file_type.is_fifo() {
+            FileKind::Fifo
+        } else if file_type.is_socket() {
+            FileKind::Socket
+        } else if file_type.is_char_device() {
+            FileKind::CharDevice
+        } else if file_type.is_block_device() {
+            FileKind::BlockDevice
+        } else {
+            FileKind::File
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn of(file_type: fs::FileType) -> Self {
+        if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_symlink() {
+            FileKind::Symlink
+        } else {
+            FileKind::File
+        }
+    }
+
+    /// FIFOs, sockets and device nodes: listed like files, but without content to read or copy.
+    pub fn is_special(self) -> bool {
+        matches!(self, FileKind::Fifo | FileKind::Socket | FileKind::CharDevice | FileKind::BlockDevice)
+    }
+
+    /// Size column text for entries whose length means nothing.
+    pub fn marker(self) -> Option<&'static str> {
+        match self {
+            FileKind::Symlink => Some("<LINK>"),
+            FileKind::Fifo => Some("<FIFO>"),
+            FileKind::Socket => Some("<SOCK>"),
+            FileKind::CharDevice => Some("<CHR>"),
+            FileKind::BlockDevice => Some("<BLK>"),
+            FileKind::File | FileKind::Dir => None,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            FileKind::File => "regular file",
+            FileKind::Dir => "directory",
+            FileKind::Symlink => "symbolic link",
+            FileKind::Fifo => "named pipe",
+            FileKind::Socket => "socket",
+            FileKind::CharDevice => "character device",
+            FileKind::BlockDevice => "block device",
+        }
+    }
+}
+
+/// Opens `path` for reading only if it is a regular file (after following links):
+/// opening a FIFO blocks until a writer shows up, and devices may never end.
+pub fn open_regular_file(path: &Path) -> Result<File, Error> {
+    let kind = FileKind::of(fs::metadata(path)?.file_type());
+    if kind != FileKind::File {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Not a regular file: {} is a {}", path.display(), kind.description())));
+    }
+    File::open(path)
+}
+
+/// Raw metadata behind the optional columns of full view mode, formatted only for rows on screen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItemDetails {
+    pub kind: FileKind,
+    pub accessed: Option<SystemTime>,
+    /// Status change time (creation time where there is none).
+    pub changed: Option<SystemTime>,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub inode: u64,
+    pub links: u64,
+}
+
+impl ItemDetails {
+    pub fn permissions(&self) -> String {
+        if self.mode == 0 { String::new() } else { permissions_string(self.mode) }
+    }
+
+    #[cfg(unix)]
+    pub fn owner(&self) -> String {
+        if self.mode == 0 { String::new() } else { user_name(self.uid) }
+    }
+
+    #[cfg(unix)]
+    pub fn group(&self) -> String {
+        if self.mode == 0 { String::new() } else { group_name(self.gid) }
+    }
+
+    #[cfg(not(unix))]
+    pub fn owner(&self) -> String {
+        String::new()
+    }
+
+    #[cfg(not(unix))]
+    pub fn group(&self) -> String {
+        String::new()
+    }
+}
+
+pub fn format_time(time: SystemTime) -> String {
+    let dt: chrono::DateTime<Local> = time.into();
+    dt.format("%d/%m/%y %H:%M").to_string()
+}
+
+/// `format_time`, empty when the time is unknown.
+pub fn format_time_opt(time: Option<SystemTime>) -> String {
+    time.map(format_time).unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn item_details(metadata: &fs::Metadata) -> ItemDetails {
+    use std::os::unix::fs::MetadataExt;
+    let changed = u64::try_from(metadata.ctime()).ok().map(|secs| SystemTime::UNIX_EPOCH + std::time::Duration::new(secs, metadata.ctime_nsec() as u32));
+    ItemDetails {
+        kind: FileKind::of(metadata.file_type()),
+        accessed: metadata.accessed().ok(),
+        changed,
+        mode: metadata.mode(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        inode: metadata.ino(),
+        links: metadata.nlink(),
+    }
+}
+
+#[cfg(not(unix))]
+fn item_details(metadata: &fs::Metadata) -> ItemDetails {
+    let kind = FileKind::of(metadata.file_type());
+    // Enough of a Unix mode for the permissions column: type and write bit
+    let type_bits = if kind == FileKind::Dir { 0o040000 } else { 0o100000 };
+    let write_bits = if metadata.permissions().readonly() { 0 } else { 0o200 };
+    ItemDetails {
+        kind,
+        accessed: metadata.accessed().ok(),
+        changed: metadata.created().ok(),
+        mode: type_bits | 0o400 | write_bits,
+        ..ItemDetails::default()
+    }
+}
+
+#[cfg(not(unix))]
+fn permissions_string(mode: u32) -> String {
+    let kind = if mode & 0o170000 == 0o040000 { 'd' } else { '-' };
+    let write = if mode & 0o200 != 0 { 'w' } else { '-' };
+    format!("{}r{}", kind, write)
+}
+
+/// `ls -l` style mode string, e.g. "drwxr-xr-x".
+#[cfg(unix)]
+fn permissions_string(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o140000 => 's',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        _ => '-',
+    };
+    let mut text = String::with_capacity(10);
+    text.push(kind);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        let execute = bits & 0o1 != 0;
+        text.push(match (mode & special != 0, execute) {
+            (true, true) => special_char,
+            (true, false) => special_char.to_ascii_uppercase(),
+            (false, true) => 'x',
+            (false, false) => '-',
         });
     }
+    text
+}
+
+/// Names looked up so far, by user or group id; misses are cached as the number.
+#[cfg(unix)]
+type NameCache = std::sync::Mutex<std::collections::HashMap<u32, String>>;
 
+/// Name for `id` from `cache`, asking `lookup` once per id. Lookups go through NSS, so they can be
+/// slow (LDAP, sssd) and are never repeated for a listing.
+#[cfg(unix)]
+fn cached_name(cache: &std::sync::OnceLock<NameCache>, id: u32, lookup: fn(u32) -> Option<String>) -> String {
+    let cache = cache.get_or_init(NameCache::default);
+    if let Some(name) = cache.lock().ok().and_then(|names| names.get(&id).cloned()) {
+        return name;
+    }
+    let name = lookup(id).unwrap_or_else(|| id.to_string());
+    if let Ok(mut names) = cache.lock() {
+        names.insert(id, name.clone());
+    }
+    name
+}
+
+/// Buffer size for `getpwuid_r`/`getgrgid_r`, doubled while the entry does not fit.
+#[cfg(unix)]
+const NSS_BUFFER_SIZE: usize = 1024;
+#[cfg(unix)]
+const NSS_BUFFER_MAX: usize = 1024 * 1024;
+
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; NSS_BUFFER_SIZE];
+    loop {
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let code = unsafe { libc::getpwuid_r(uid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+        if code == libc::ERANGE && buffer.len() < NSS_BUFFER_MAX {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() || entry.pw_name.is_null() {
+            return None;
+        }
+        return Some(unsafe { std::ffi::CStr::from_ptr(entry.pw_name) }.to_string_lossy().into_owned());
+    }
+}
+
+#[cfg(unix)]
+fn lookup_group(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; NSS_BUFFER_SIZE];
+    loop {
+        let mut entry: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let code = unsafe { libc::getgrgid_r(gid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+        if code == libc::ERANGE && buffer.len() < NSS_BUFFER_MAX {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() || entry.gr_name.is_null() {
+            return None;
+        }
+        return Some(unsafe { std::ffi::CStr::from_ptr(entry.gr_name) }.to_string_lossy().into_owned());
+    }
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    static USERS: std::sync::OnceLock<NameCache> = std::sync::OnceLock::new();
+    cached_name(&USERS, uid, lookup_user)
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> String {
+    static GROUPS: std::sync::OnceLock<NameCache> = std::sync::OnceLock::new();
+    cached_name(&GROUPS, gid, lookup_group)
+}
+
+/// Loads a directory listing, returns (items, number of entries before filtering, the error that cut
+/// the listing short). Reading stops at the first failing entry, the rows read until then are kept.
+pub fn load_directory_rows(path: &Path, filter: &PanelFilter) -> Result<(Vec<Item>, usize, Option<String>), Error> {
+    let mut entries = Vec::new();
+    let mut error = None;
+    for entry in read_dir(path)? {
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    let total = entries.len();
+
+    let mut children = Vec::with_capacity(entries.len() + 1);
+
+    // Don't add ".." on root folder.
+    if let Some(parent) = path.parent() {
+        children.push(parent_item(parent.to_path_buf()));
+    }
+    children.extend(entries.iter().filter_map(|entry| entry_row(entry, filter)));
+    sort_rows(&mut children);
+
+    Ok((children, total, error))
+}
+
+/// Row for a directory entry with a single metadata() call (one stat syscall), `None` if the filter hides it.
+/// When the entry cannot be stat'ed (e.g. a directory readable but not searchable) the row keeps the
+/// type from the directory itself and carries the reason.
+pub fn entry_row(entry: &DirEntry, filter: &PanelFilter) -> Option<Item> {
+    let entry_path = entry.path();
+    let metadata = entry.metadata();
+    let file_type = match &metadata {
+        Ok(metadata) => Some(metadata.file_type()),
+        Err(_) => entry.file_type().ok(),
+    };
+    let is_dir = file_type.is_some_and(|file_type| file_type.is_dir());
+    let name_full = display_name(&entry.file_name());
+    if !filter.matches(&name_full, is_dir) {
+        return None;
+    }
+    let mut item = item_from_metadata(entry_path, name_full, metadata.as_ref().ok());
+    if let Err(e) = metadata {
+        item.is_dir = is_dir;
+        item.details.kind = file_type.map(FileKind::of).unwrap_or_default();
+        item.error = Some(e.to_string());
+    }
+    Some(item)
+}
+
+/// Directories first, then files by extension and name; a leading ".." stays put.
+pub fn sort_rows(rows: &mut [Item]) {
     // Sort items on already-computed fields (no stat syscalls during sort)
-    let sort_start = usize::from(has_parent);
-    children[sort_start..].sort_by(|a, b| match (a.is_dir, b.is_dir) {
+    let sort_start = usize::from(rows.first().is_some_and(|item| item.name == ".."));
+    rows[sort_start..].sort_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         (true, true) => a.name_full.to_lowercase().cmp(&b.name_full.to_lowercase()),
@@ -68,8 +395,107 @@ pub fn load_directory_rows(path: &Path) -> Result<Vec<Item>, Error> {
             })
         }
     });
+}
 
-    Ok(children)
+/// Rows for a panelized list: `paths` in their given order, named relative to `base` when under it.
+/// Paths that no longer exist are skipped; returns (items, number of paths before filtering).
+pub fn load_list_rows(base: &Path, paths: &[PathBuf], filter: &PanelFilter) -> (Vec<Item>, usize) {
+    let mut items = Vec::with_capacity(paths.len() + 1);
+    // ".." leaves the list and returns to the panel's directory
+    items.push(parent_item(base.to_path_buf()));
+    for path in paths {
+        let Ok(metadata) = symlink_metadata(path) else { continue };
+        let file_name = path.file_name().map(display_name).unwrap_or_default();
+        if !filter.matches(&file_name, metadata.is_dir()) {
+            continue;
+        }
+        let name_full = display_name(path.strip_prefix(base).unwrap_or(path).as_os_str());
+        items.push(item_from_metadata(path.clone(), name_full, Some(&metadata)));
+    }
+    (items, paths.len())
+}
+
+/// The ".." row; `path` is where entering it leads.
+pub fn parent_item(path: PathBuf) -> Item {
+    Item {
+        path,
+        name_full: "..".to_string(),
+        name: "..".to_string(),
+        extension: String::new(),
+        is_dir: true,
+        size_bytes: 0,
+        modified: None,
+        depth: 0,
+        details: ItemDetails::default(),
+        error: None,
+    }
+}
+
+/// Builds a row from already-read metadata. `name` is `name_full` without the extension,
+/// so rows named by a relative path keep their directory part.
+fn item_from_metadata(path: PathBuf, name_full: String, metadata: Option<&fs::Metadata>) -> Item {
+    let is_dir = metadata.map(|m| m.is_dir()).unwrap_or(false);
+    let extension = if is_dir { String::new() } else { path.extension().and_then(|e| e.to_str()).unwrap_or("").to_string() };
+    // Escaping can make the shown extension longer than the raw one, but never adds a dot
+    let name = match name_full.rfind('.') {
+        Some(dot) if !extension.is_empty() => name_full[..dot].to_string(),
+        _ => name_full.clone(),
+    };
+    let size_bytes = if is_dir { 0 } else { metadata.map(|m| m.len()).unwrap_or(0) };
+    let modified = metadata.and_then(|m| m.modified().ok());
+    let details = metadata.map(item_details).unwrap_or_default();
+
+    Item {
+        path,
+        name_full,
+        name,
+        extension,
+        is_dir,
+        size_bytes,
+        modified,
+        depth: 0,
+        details,
+        error: None,
+    }
+}
+
+/// Like `load_directory_rows`, with the contents of every directory in `expanded` listed
+/// under it. Nested rows carry a `name_full` relative to `path` and a `depth`.
+pub fn load_tree_rows(path: &Path, filter: &PanelFilter, expanded: &HashSet<PathBuf>) -> Result<(Vec<Item>, usize, Option<String>), Error> {
+    let (rows, total, error) = load_directory_rows(path, filter)?;
+    let mut items = Vec::with_capacity(rows.len());
+    for item in rows {
+        push_tree_item(item, filter, expanded, &mut items);
+    }
+    Ok((items, total, error))
+}
+
+fn push_tree_item(mut item: Item, filter: &PanelFilter, expanded: &HashSet<PathBuf>, items: &mut Vec<Item>) {
+    let dir = item.path.clone();
+    let is_open = item.is_dir && item.name != ".." && expanded.contains(&dir);
+    let (depth, prefix) = (item.depth, PathBuf::from(&item.name_full));
+    if !is_open {
+        items.push(item);
+        return;
+    }
+
+    // Unreadable directories show up empty, with the reason on their own row
+    let children = match load_directory_rows(&dir, filter) {
+        Ok((children, _, error)) => {
+            item.error = item.error.take().or(error);
+            children
+        }
+        Err(e) => {
+            item.error = Some(e.to_string());
+            Vec::new()
+        }
+    };
+    items.push(item);
+    for mut child in children.into_iter().filter(|child| child.name != "..") {
+        child.name_full = prefix.join(&child.name_full).to_string_lossy().into_owned();
+        child.depth = depth + 1;
+        push_tree_item(child, filter, expanded, items);
+    }
 }
 
 pub fn get_current_dir() -> Result<PathBuf, Error> {
@@ -95,26 +521,81 @@ pub fn create_directory(path: PathBuf) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn copy_path(source: PathBuf, dest: PathBuf, is_dir: bool) -> Result<(), Error> {
+/// Copies over an existing destination; directories are merged. Device nodes are
+/// recreated only with `allow_devices` (`copy_devices=true` in the settings file).
+pub fn copy_path(source: PathBuf, dest: PathBuf, is_dir: bool, allow_devices: bool) -> Result<(), Error> {
+    // Copying onto itself would truncate the source, into itself would never finish
+    let resolved_dest = dest.parent().and_then(|parent| parent.canonicalize().ok()).zip(dest.file_name()).map(|(parent, name)| parent.join(name));
+    if let (Ok(resolved_source), Some(resolved_dest)) = (source.canonicalize(), resolved_dest) && resolved_dest.starts_with(&resolved_source) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Cannot copy {} onto itself", source.display())));
+    }
     if is_dir {
-        copy_dir_recursive(&source, &dest)
+        copy_dir_recursive(&source, &dest, allow_devices)
     } else {
-        copy_file_content(&source, &dest)
+        copy_file(&source, &dest, allow_devices)
+    }
+}
+
+/// Copies anything but a directory according to what it is (links followed): FIFOs are
+/// recreated empty, sockets skipped (only their server can bring them back) and device
+/// nodes refused unless allowed.
+fn copy_file(source: &Path, dest: &Path, allow_devices: bool) -> Result<(), Error> {
+    let metadata = fs::metadata(source)?;
+    match FileKind::of(metadata.file_type()) {
+        FileKind::Socket => Ok(()),
+        FileKind::Fifo => make_node(dest, &metadata),
+        FileKind::CharDevice | FileKind::BlockDevice if allow_devices => make_node(dest, &metadata),
+        kind @ (FileKind::CharDevice | FileKind::BlockDevice) => Err(Error::new(ErrorKind::InvalidInput, format!("Refusing to copy {} {} (copy_devices=true allows it)", kind.description(), source.display()))),
+        _ => copy_file_content(source, dest),
+    }
+}
+
+/// Creates a FIFO or device node like `metadata`'s at `dest`, replacing an existing file.
+#[cfg(unix)]
+fn make_node(dest: &Path, metadata: &fs::Metadata) -> Result<(), Error> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+
+    if symlink_metadata(dest).is_ok_and(|existing| !existing.is_dir()) {
+        remove_file(dest)?;
     }
+    let c_path = CString::new(dest.as_os_str().as_bytes())?;
+    // Field widths differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let (mode, device) = (metadata.mode() as libc::mode_t, metadata.rdev() as libc::dev_t);
+    // SAFETY: c_path is NUL-terminated; mode carries the node type, which mknod validates
+    if unsafe { libc::mknod(c_path.as_ptr(), mode, device) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_node(dest: &Path, _metadata: &fs::Metadata) -> Result<(), Error> {
+    Err(Error::new(ErrorKind::Unsupported, format!("Cannot create {}: special files are not supported here", dest.display())))
 }
 
 /// Copy file content without trying to preserve Unix permissions.
 /// This works across filesystems (e.g., ext4 to exFAT) where permission
 /// preservation would fail with EPERM.
 /// Uses io::copy which leverages copy_file_range (zero-copy) on Linux.
+/// The modification time is kept (best effort) so size+time comparisons see the copy as equal.
 fn copy_file_content(source: &Path, dest: &Path) -> Result<(), Error> {
     let mut src_file = File::open(source)?;
+    // An existing symlink is replaced, not written through to whatever it points at
+    if dest.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        remove_file(dest)?;
+    }
     let mut dst_file = File::create(dest)?;
     io::copy(&mut src_file, &mut dst_file)?;
+    if let Ok(modified) = src_file.metadata().and_then(|metadata| metadata.modified()) {
+        let _ = dst_file.set_modified(modified);
+    }
     Ok(())
 }
 
-fn copy_dir_recursive(source: &Path, dest: &Path) -> Result<(), Error> {
+fn copy_dir_recursive(source: &Path, dest: &Path, allow_devices: bool) -> Result<(), Error> {
     fs::create_dir_all(dest)?;
 
     for entry in read_dir(source)? {
@@ -123,16 +604,16 @@ fn copy_dir_recursive(source: &Path, dest: &Path) -> Result<(), Error> {
         let dest_path = dest.join(entry.file_name());
 
         if entry_path.is_dir() {
-            copy_dir_recursive(&entry_path, &dest_path)?;
+            copy_dir_recursive(&entry_path, &dest_path, allow_devices)?;
         } else {
-            copy_file_content(&entry_path, &dest_path)?;
+            copy_file(&entry_path, &dest_path, allow_devices)?;
         }
     }
 
     Ok(())
 }
 
-pub fn move_path(source: PathBuf, dest: PathBuf, is_dir: bool) -> Result<(), Error> {
+pub fn move_path(source: PathBuf, dest: PathBuf, is_dir: bool, allow_devices: bool) -> Result<(), Error> {
     // Try rename first (fast, same filesystem)
     match rename(&source, &dest) {
         Ok(_) => Ok(()),
@@ -142,7 +623,7 @@ pub fn move_path(source: PathBuf, dest: PathBuf, is_dir: bool) -> Result<(), Err
             // - ERROR_NOT_SAME_DEVICE (17) on Windows
             if matches!(e.raw_os_error(), Some(17) | Some(18)) {
                 // Cross-device move: copy then delete
-                copy_path(source.clone(), dest.clone(), is_dir)?;
+                copy_path(source.clone(), dest.clone(), is_dir, allow_devices)?;
 
                 // Delete source - if this fails, the copy succeeded but source remains
                 if let Err(del_err) = delete_path(source, is_dir) {
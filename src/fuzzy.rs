@@ -0,0 +1,229 @@
+use crate::app::TextInput;
+use crate::gitignore::{ancestor_gitignores, is_ignored, with_dir_rules};
+use crate::utils::{display_name, fuzzy_match};
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Directories never worth indexing, even without a .gitignore.
+const SKIPPED_DIRS: [&str; 5] = [".git", ".hg", ".svn", "node_modules", "target"];
+const MAX_INDEXED: usize = 200_000;
+const MAX_MATCHES: usize = 1000;
+const BATCH_SIZE: usize = 512;
+/// While indexing, newly found files are ranked at most this often.
+const RANK_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct FuzzyMatch {
+    /// The file and its path relative to the root, for display.
+    pub path: PathBuf,
+    pub display: String,
+    /// Char indices into `display` that matched the query.
+    pub positions: Vec<usize>,
+}
+
+enum FinderMessage {
+    /// Files indexed so far.
+    Indexed(usize),
+    /// Best matches for a query, plus how many files matched in all.
+    Ranked(String, Vec<FuzzyMatch>, usize),
+    Done,
+}
+
+/// Ctrl+P popup state: a background file index of `root`, ranked against `query` by the same worker.
+pub struct FuzzyFinder {
+    pub root: PathBuf,
+    pub query: TextInput,
+    pub indexed: usize,
+    /// The best `MAX_MATCHES` matches of the last ranking.
+    pub matches: Vec<FuzzyMatch>,
+    /// Matches before truncation to `matches`.
+    pub match_count: usize,
+    pub selected: usize,
+    pub done: bool,
+    queries: Sender<String>,
+    receiver: Receiver<FinderMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl FuzzyFinder {
+    pub fn start(root: PathBuf) -> Self {
+        let (sender, receiver) = channel();
+        let (queries, query_receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_root = root.clone();
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || run_finder(thread_root, query_receiver, sender, thread_cancel));
+
+        Self { root, query: TextInput::new(), indexed: 0, matches: Vec::new(), match_count: 0, selected: 0, done: false, queries, receiver, cancel }
+    }
+
+    /// Drains index progress and rankings; rankings of an outdated query are dropped.
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                FinderMessage::Indexed(indexed) => self.indexed = indexed,
+                FinderMessage::Ranked(query, matches, match_count) if query == self.query.text => {
+                    self.matches = matches;
+                    self.match_count = match_count;
+                    self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+                }
+                FinderMessage::Ranked(..) => {}
+                FinderMessage::Done => self.done = true,
+            }
+        }
+    }
+
+    /// Call after editing `query`; the worker re-ranks and `poll` picks up the result.
+    pub fn query_changed(&mut self) {
+        self.selected = 0;
+        let _ = self.queries.send(self.query.text.clone());
+    }
+
+    pub fn select_by(&mut self, delta: isize) {
+        let max = self.matches.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, max) as usize;
+    }
+
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.matches.get(self.selected).map(|found| found.path.clone())
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for FuzzyFinder {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// The worker's file index and the query it was last asked to rank.
+struct Index {
+    files: Vec<(PathBuf, String)>,
+    query: String,
+    /// Files or query changed since the last ranking.
+    dirty: bool,
+    ranked_at: Instant,
+}
+
+impl Index {
+    /// Takes the newest pending query; false once the finder is gone.
+    fn take_queries(&mut self, queries: &Receiver<String>) -> bool {
+        loop {
+            match queries.try_recv() {
+                Ok(query) => {
+                    self.query = query;
+                    self.dirty = true;
+                    // A new query is ranked right away, even mid-index
+                    self.ranked_at = Instant::now() - RANK_INTERVAL;
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    /// Sends the ranking if anything changed and it is due; false once the finder is gone.
+    fn rank_if_due(&mut self, sender: &Sender<FinderMessage>, force: bool) -> bool {
+        if !self.dirty || (!force && self.ranked_at.elapsed() < RANK_INTERVAL) {
+            return true;
+        }
+        self.dirty = false;
+        self.ranked_at = Instant::now();
+        let query = self.query.as_str();
+        let mut scored: Vec<(usize, i32, Vec<usize>)> = self.files.iter().enumerate()
+            .filter_map(|(index, (_, display))| fuzzy_match(query, display).map(|(score, positions)| (index, score, positions)))
+            .collect();
+        let match_count = scored.len();
+        if !query.trim().is_empty() {
+            let files = &self.files;
+            let by_rank = |a: &(usize, i32, Vec<usize>), b: &(usize, i32, Vec<usize>)| b.1.cmp(&a.1).then_with(|| files[a.0].1.len().cmp(&files[b.0].1.len()));
+            if scored.len() > MAX_MATCHES {
+                scored.select_nth_unstable_by(MAX_MATCHES, by_rank);
+                scored.truncate(MAX_MATCHES);
+            }
+            scored.sort_by(by_rank);
+        }
+        scored.truncate(MAX_MATCHES);
+        let matches = scored.into_iter()
+            .map(|(index, _, positions)| {
+                let (path, display) = &self.files[index];
+                FuzzyMatch { path: path.clone(), display: display.clone(), positions }
+            })
+            .collect();
+        sender.send(FinderMessage::Ranked(self.query.clone(), matches, match_count)).is_ok()
+    }
+}
+
+/// Indexes `root`, then keeps ranking the index against each new query until the finder is dropped.
+fn run_finder(root: PathBuf, queries: Receiver<String>, sender: Sender<FinderMessage>, cancel: Arc<AtomicBool>) {
+    let mut index = Index { files: Vec::new(), query: String::new(), dirty: true, ranked_at: Instant::now() };
+    if !run_index(&root, &mut index, &queries, &sender, &cancel) {
+        return;
+    }
+    if !index.rank_if_due(&sender, true) || sender.send(FinderMessage::Done).is_err() {
+        return;
+    }
+    while let Ok(query) = queries.recv() {
+        index.query = query;
+        index.dirty = true;
+        if cancel.load(Ordering::Relaxed) || !index.take_queries(&queries) || !index.rank_if_due(&sender, true) {
+            return;
+        }
+    }
+}
+
+/// Depth-first walk collecting files, honoring .gitignore and `SKIPPED_DIRS`; false once cancelled or the finder is gone.
+fn run_index(root: &Path, index: &mut Index, queries: &Receiver<String>, sender: &Sender<FinderMessage>, cancel: &AtomicBool) -> bool {
+    let mut stack = vec![(root.to_path_buf(), Arc::new(ancestor_gitignores(root)))];
+
+    while let Some((dir, inherited_rules)) = stack.pop() {
+        // Queries typed during a slow walk are answered between directories, not only every batch
+        if !index.take_queries(queries) || !index.rank_if_due(sender, false) {
+            return false;
+        }
+        let Ok(entries) = read_dir(&dir) else { continue };
+        let rules = with_dir_rules(&dir, inherited_rules);
+
+        for entry in entries.flatten() {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            // Symlinks are listed but never followed
+            let Ok(file_type) = entry.file_type() else { continue };
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = file_type.is_dir();
+            if (is_dir && SKIPPED_DIRS.contains(&name.as_str())) || is_ignored(&rules, &path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                stack.push((path, Arc::clone(&rules)));
+                continue;
+            }
+
+            let display = relative_display(root, &path);
+            index.files.push((path, display));
+            index.dirty = true;
+            if index.files.len().is_multiple_of(BATCH_SIZE) && (sender.send(FinderMessage::Indexed(index.files.len())).is_err() || !index.rank_if_due(sender, false)) {
+                return false;
+            }
+            if index.files.len() >= MAX_INDEXED {
+                stack.clear();
+                break;
+            }
+        }
+    }
+
+    sender.send(FinderMessage::Indexed(index.files.len())).is_ok() && index.take_queries(queries)
+}
+
+fn relative_display(root: &Path, path: &Path) -> String {
+    display_name(path.strip_prefix(root).unwrap_or(path).as_os_str())
+}
@@ -0,0 +1,47 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf, absolute};
+use std::sync::Arc;
+
+/// Compiled ignore files from the outermost directory to the innermost; later ones take precedence.
+pub type IgnoreRules = Vec<Gitignore>;
+
+/// Matcher for the existing `files` with patterns relative to `dir`, None if they hold no patterns.
+fn read_ignore_files(dir: &Path, files: &[PathBuf]) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(dir);
+    for file in files.iter().filter(|file| file.is_file()) {
+        // A file with some bad lines still contributes the good ones
+        let _ = builder.add(file);
+    }
+    builder.build().ok().filter(|gitignore| !gitignore.is_empty())
+}
+
+/// Rules from the enclosing repository above `start_dir`: its .git/info/exclude and the .gitignore files on the way down.
+pub fn ancestor_gitignores(start_dir: &Path) -> IgnoreRules {
+    // Not canonicalized, so the rules' roots stay prefixes of the paths a walk from `start_dir` produces
+    let Ok(start) = absolute(start_dir) else { return Vec::new() };
+    let Some(root) = start.ancestors().find(|dir| dir.join(".git").exists()) else { return Vec::new() };
+    let mut rules: IgnoreRules = read_ignore_files(root, &[root.join(".git").join("info").join("exclude")]).into_iter().collect();
+    let mut ancestors: Vec<&Path> = start.ancestors().skip(1).take_while(|dir| dir.starts_with(root)).collect();
+    ancestors.reverse();
+    for dir in ancestors {
+        rules.extend(read_ignore_files(dir, &[dir.join(".gitignore")]));
+    }
+    rules
+}
+
+/// The innermost ignore file with a matching pattern decides, and within it the last matching line, as in git.
+pub fn is_ignored(rules: &[Gitignore], path: &Path, is_dir: bool) -> bool {
+    rules.iter().rev().map(|gitignore| gitignore.matched(path, is_dir)).find(|found| !found.is_none()).is_some_and(|found| found.is_ignore())
+}
+
+/// `inherited` plus the rules of `dir`'s own .gitignore, which apply to everything below it.
+pub fn with_dir_rules(dir: &Path, inherited: Arc<IgnoreRules>) -> Arc<IgnoreRules> {
+    match read_ignore_files(dir, &[dir.join(".gitignore")]) {
+        Some(gitignore) => {
+            let mut rules = inherited.as_ref().clone();
+            rules.push(gitignore);
+            Arc::new(rules)
+        }
+        None => inherited,
+    }
+}
@@ -1,6 +1,10 @@
-use crate::app::{AppState, Item};
-use crate::fs_ops::{copy_path, create_directory, delete_path, load_directory_rows, move_path, rename_path};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseEventKind};
+use crate::app::{AppState, FuzzyAction};
+use crate::compare::{CompareFlag, entries_to_copy};
+use crate::fs_ops::{copy_path, create_directory, delete_path, move_path, rename_path};
+use crate::utils::{display_name, name_from_display};
+use crate::viewer::HEX_ROW_BYTES;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
+use ratatui::layout::Position;
 use ratatui::widgets::TableState;
 use std::io::Result;
 use std::path::PathBuf;
@@ -51,30 +55,76 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                         KeyCode::Right => app_state.create_input.move_right(),
                         _ => {}
                     }
-                } else if app_state.is_f3_displayed {
+                } else if app_state.is_find_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_find_key(app_state, key);
+                } else if app_state.is_fuzzy_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_fuzzy_key(app_state, key);
+                } else if app_state.is_columns_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_columns_key(app_state, key);
+                } else if app_state.is_sync_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_sync_key(app_state, key);
+                } else if app_state.is_compare_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_compare_key(app_state, key);
+                } else if app_state.is_mounts_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_mounts_key(app_state, key);
+                } else if app_state.is_panelize_displayed {
                     match key.code {
                         KeyCode::Esc => handle_esc(app_state),
-                        KeyCode::F(3) => app_state.close_viewer(),
                         KeyCode::F(10) => return Ok(false),
-                        KeyCode::Down => app_state.viewer_scroll_down(),
-                        KeyCode::Up => app_state.viewer_scroll_up(),
-                        KeyCode::Left => app_state.viewer_scroll_left(),
-                        KeyCode::Right => app_state.viewer_scroll_right(),
-                        KeyCode::PageDown => app_state.viewer_page_down(),
-                        KeyCode::PageUp => app_state.viewer_page_up(),
-                        KeyCode::Home => app_state.viewer_home(),
-                        KeyCode::End => app_state.viewer_end(),
+                        // A running command only listens for cancel
+                        _ if app_state.panelize_run.is_some() => {}
+                        KeyCode::Enter => app_state.apply_panelize(),
+                        KeyCode::Char(to_insert) => app_state.panelize_input.insert(to_insert),
+                        KeyCode::Backspace => app_state.panelize_input.backspace(),
+                        KeyCode::Delete => app_state.panelize_input.delete_forward(),
+                        KeyCode::Left => app_state.panelize_input.move_left(),
+                        KeyCode::Right => app_state.panelize_input.move_right(),
                         _ => {}
                     }
+                } else if app_state.is_filter_displayed {
+                    match key.code {
+                        KeyCode::Esc => handle_esc(app_state),
+                        KeyCode::F(10) => return Ok(false),
+                        KeyCode::Enter => app_state.apply_filter(),
+                        KeyCode::Char(to_insert) => app_state.filter_input.insert(to_insert),
+                        KeyCode::Backspace => app_state.filter_input.backspace(),
+                        KeyCode::Delete => app_state.filter_input.delete_forward(),
+                        KeyCode::Left => app_state.filter_input.move_left(),
+                        KeyCode::Right => app_state.filter_input.move_right(),
+                        _ => {}
+                    }
+                } else if app_state.is_du_displayed {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_disk_usage_key(app_state, key);
                 } else if app_state.is_editor_save_prompt {
                     match key.code {
                         KeyCode::Char('y') | KeyCode::Char('Y') => {
-                            // Save and close
-                            if let Err(e) = app_state.editor_save() {
-                                app_state.display_error(e);
-                            }
+                            // Save and close; a failed save keeps the editor and its changes open
                             app_state.is_editor_save_prompt = false;
-                            app_state.close_editor();
+                            match app_state.editor_save() {
+                                Ok(()) => app_state.close_editor(),
+                                Err(e) => app_state.display_error(e),
+                            }
                         }
                         KeyCode::Char('n') | KeyCode::Char('N') => {
                             // Discard and close
@@ -87,6 +137,40 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                         }
                         _ => {}
                     }
+                } else if app_state.is_viewer_goto_displayed {
+                    match key.code {
+                        KeyCode::Esc => app_state.is_viewer_goto_displayed = false,
+                        KeyCode::Enter => app_state.viewer_goto(),
+                        KeyCode::Char(c) => app_state.viewer_goto_input.insert(c),
+                        KeyCode::Backspace => app_state.viewer_goto_input.backspace(),
+                        KeyCode::Delete => app_state.viewer_goto_input.delete_forward(),
+                        KeyCode::Left => app_state.viewer_goto_input.move_left(),
+                        KeyCode::Right => app_state.viewer_goto_input.move_right(),
+                        _ => {}
+                    }
+                } else if app_state.is_f3_displayed && app_state.is_hex_editing() {
+                    if key.code == KeyCode::F(10) {
+                        return Ok(false);
+                    }
+                    handle_hex_edit_key(app_state, key);
+                } else if app_state.is_f3_displayed {
+                    match key.code {
+                        KeyCode::Esc => handle_esc(app_state),
+                        KeyCode::F(3) => app_state.close_viewer(),
+                        KeyCode::F(2) => app_state.viewer_start_hex_edit(),
+                        KeyCode::F(4) => app_state.viewer_toggle_mode(),
+                        KeyCode::F(5) => app_state.open_viewer_goto(),
+                        KeyCode::F(10) => return Ok(false),
+                        KeyCode::Down => app_state.viewer_scroll_down(),
+                        KeyCode::Up => app_state.viewer_scroll_up(),
+                        KeyCode::Left => app_state.viewer_scroll_left(),
+                        KeyCode::Right => app_state.viewer_scroll_right(),
+                        KeyCode::PageDown => app_state.viewer_page_down(),
+                        KeyCode::PageUp => app_state.viewer_page_up(),
+                        KeyCode::Home => app_state.viewer_home(),
+                        KeyCode::End => app_state.viewer_end(),
+                        _ => {}
+                    }
                 } else if app_state.is_f4_displayed {
                     if let Some(state) = &mut app_state.editor_state {
                         state.auto_scroll = true;
@@ -150,17 +234,54 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                             app_state.search_clear();
                             handle_esc(app_state);
                         }
+                        KeyCode::F(1) if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_mounts(true),
+                        KeyCode::F(2) if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_mounts(false),
                         KeyCode::F(1) => toggle_help(app_state),
                         KeyCode::F(2) => toggle_rename(app_state),
                         KeyCode::F(3) => handle_f3_view(app_state),
                         KeyCode::F(4) => handle_f4_edit(app_state),
                         KeyCode::F(5) => toggle_copy(app_state),
                         KeyCode::F(6) => toggle_move(app_state),
+                        KeyCode::F(7) if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_find(),
                         KeyCode::F(7) => toggle_create(app_state),
                         KeyCode::F(8) | KeyCode::Delete => toggle_delete(app_state),
                         KeyCode::F(9) => open_terminal(app_state),
                         KeyCode::F(10) => return Ok(false),
-                        KeyCode::Char('q') => return Ok(false), // Temp debug
+                        KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.toggle_quick_view(),
+                        KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.toggle_hidden(),
+                        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.open_filter(),
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.open_fuzzy(),
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_panelize(),
+                        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::ALT) => app_state.toggle_tree_view(),
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.resize_split(-5),
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.resize_split(5),
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::ALT) => app_state.toggle_zoom(),
+                        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::ALT) => app_state.toggle_split_orientation(),
+                        KeyCode::Left if app_state.is_tree_active() => app_state.tree_collapse(),
+                        KeyCode::Right if app_state.is_tree_active() => app_state.tree_expand(),
+                        KeyCode::Char('m') if key.modifiers.contains(KeyModifiers::ALT) => app_state.toggle_miller(),
+                        KeyCode::Left if app_state.is_miller => handle_navigate_up(app_state),
+                        KeyCode::Right if app_state.is_miller => handle_miller_right(app_state),
+                        KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::ALT) => app_state.cycle_view_mode(),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_columns(),
+                        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_compare(),
+                        KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_sync(),
+                        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::ALT) => app_state.open_disk_usage(),
+                        KeyCode::Left if app_state.is_brief_active() => {
+                            // Brief mode columns are one page tall
+                            let page_size = app_state.page_size as usize;
+                            handle_move_selection(app_state, |state, _len| {
+                                state.select(state.selected().map(|selected| selected.saturating_sub(page_size)));
+                            })
+                        }
+                        KeyCode::Right if app_state.is_brief_active() => {
+                            let page_size = app_state.page_size as usize;
+                            handle_move_selection(app_state, |state, len| {
+                                state.select(state.selected().map(|selected| (selected + page_size).min(len.saturating_sub(1))));
+                            })
+                        }
+                        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.cycle_search_mode(),
+                        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.toggle_search_filter(),
                         KeyCode::Char(' ') => {
                             // Space toggles selection and moves to next item
                             app_state.toggle_selection();
@@ -169,7 +290,7 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                             // Insert toggles selection without calculating directory size
                             app_state.toggle_selection_no_size();
                         }
-                        KeyCode::Char(c) if c.is_alphanumeric() || ".-_".contains(c) => {
+                        KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
                             app_state.search_add_char(c);
                         }
                         KeyCode::Backspace => {
@@ -225,15 +346,26 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                 MouseEventKind::Down(_btn) => {
                     if app_state.is_f4_displayed {
                         handle_editor_click(app_state, mouse_event.column, mouse_event.row);
+                    } else if is_on_split_separator(app_state, mouse_event.column, mouse_event.row) {
+                        app_state.is_dragging_split = true;
                     } else {
                         handle_mouse_click(app_state, mouse_event.column, mouse_event.row);
                     }
                 }
+                MouseEventKind::Drag(_btn) if app_state.is_dragging_split => app_state.drag_split(mouse_event.column, mouse_event.row),
+                MouseEventKind::Up(_btn) if app_state.is_dragging_split => {
+                    app_state.is_dragging_split = false;
+                    app_state.save_settings();
+                }
                 MouseEventKind::ScrollDown => {
                     if app_state.is_f3_displayed {
                         app_state.viewer_scroll_down();
                     } else if app_state.is_f4_displayed {
                         app_state.editor_scroll_down();
+                    } else if app_state.is_du_displayed {
+                        if let Some(view) = &mut app_state.du_view {
+                            view.select_by(1);
+                        }
                     } else {
                         handle_move_selection(app_state, |state, len| {
                             state.select(state.selected().map_or(Some(0), |i| Some((i + 1).min(len.saturating_sub(1)))));
@@ -245,6 +377,10 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
                         app_state.viewer_scroll_up();
                     } else if app_state.is_f4_displayed {
                         app_state.editor_scroll_up();
+                    } else if app_state.is_du_displayed {
+                        if let Some(view) = &mut app_state.du_view {
+                            view.select_by(-1);
+                        }
                     } else {
                         handle_move_selection(app_state, |state, _len| {
                             state.select(state.selected().map_or(Some(0), |i| Some(i.saturating_sub(1))));
@@ -277,6 +413,285 @@ pub fn handle_input(app_state: &mut AppState) -> Result<bool> {
     Ok(true)
 }
 
+fn handle_find_key(app_state: &mut AppState, key: KeyEvent) {
+    if let Some(search) = &mut app_state.find_search {
+        // Result list
+        let page = app_state.page_size.max(1) as isize;
+        match key.code {
+            KeyCode::Esc => app_state.stop_find(),
+            KeyCode::Down => search.select_by(1),
+            KeyCode::Up => search.select_by(-1),
+            KeyCode::PageDown => search.select_by(page),
+            KeyCode::PageUp => search.select_by(-page),
+            KeyCode::Home => search.selected = 0,
+            KeyCode::End => search.select_by(isize::MAX / 2),
+            KeyCode::Enter => {
+                if let Some(path) = search.selected_result().map(|result| result.path.clone()) {
+                    app_state.reset_find();
+                    app_state.go_to_path(&path);
+                }
+            }
+            KeyCode::F(3) => app_state.open_find_result(false),
+            KeyCode::F(4) => app_state.open_find_result(true),
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::ALT) => app_state.panelize_find_results(),
+            _ => {}
+        }
+        return;
+    }
+
+    // Search form
+    let dialog = &mut app_state.find_dialog;
+    let is_toggle = dialog.focused().is_toggle();
+    match key.code {
+        KeyCode::Esc => app_state.reset_find(),
+        KeyCode::Enter => app_state.start_find(),
+        KeyCode::Tab | KeyCode::Down => dialog.focus_next(),
+        KeyCode::BackTab | KeyCode::Up => dialog.focus_prev(),
+        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right if is_toggle => dialog.toggle_focused(),
+        KeyCode::Char(c) => {
+            if let Some(input) = dialog.focused_input_mut() {
+                input.insert(c);
+            }
+        }
+        KeyCode::Backspace => {
+            if let Some(input) = dialog.focused_input_mut() {
+                input.backspace();
+            }
+        }
+        KeyCode::Delete => {
+            if let Some(input) = dialog.focused_input_mut() {
+                input.delete_forward();
+            }
+        }
+        KeyCode::Left => {
+            if let Some(input) = dialog.focused_input_mut() {
+                input.move_left();
+            }
+        }
+        KeyCode::Right => {
+            if let Some(input) = dialog.focused_input_mut() {
+                input.move_right();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_columns_key(app_state: &mut AppState, key: KeyEvent) {
+    let cursor = app_state.columns_cursor;
+    let view = app_state.active_view_mut();
+    let last = view.columns.len().saturating_sub(1);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter => app_state.is_columns_displayed = false,
+        KeyCode::Up if shift => app_state.columns_cursor = view.move_column(cursor, -1),
+        KeyCode::Down if shift => app_state.columns_cursor = view.move_column(cursor, 1),
+        KeyCode::Up => app_state.columns_cursor = cursor.saturating_sub(1),
+        KeyCode::Down => app_state.columns_cursor = (cursor + 1).min(last),
+        KeyCode::Char(' ') => view.toggle_column(cursor),
+        KeyCode::Left => view.resize_column(cursor, -1),
+        KeyCode::Right => view.resize_column(cursor, 1),
+        _ => {}
+    }
+}
+
+fn handle_hex_edit_key(app_state: &mut AppState, key: KeyEvent) {
+    let viewport = app_state.viewer_viewport_height;
+    let page = (viewport.max(1) as u64 * HEX_ROW_BYTES) as i64;
+    let is_control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc | KeyCode::F(4) => {
+            if app_state.editor_is_modified() {
+                app_state.is_editor_save_prompt = true;
+            } else {
+                app_state.close_editor();
+            }
+        }
+        KeyCode::F(2) => save_editor(app_state),
+        KeyCode::Char('s') if is_control => save_editor(app_state),
+        KeyCode::F(5) => app_state.open_viewer_goto(),
+        _ => {
+            let Some(state) = &mut app_state.viewer_state else { return };
+            match key.code {
+                KeyCode::Left => state.hex_move(-1, viewport),
+                KeyCode::Right => state.hex_move(1, viewport),
+                KeyCode::Up => state.hex_move(-(HEX_ROW_BYTES as i64), viewport),
+                KeyCode::Down => state.hex_move(HEX_ROW_BYTES as i64, viewport),
+                KeyCode::PageUp => state.hex_move(-page, viewport),
+                KeyCode::PageDown => state.hex_move(page, viewport),
+                KeyCode::Home if is_control => state.hex_move(i64::MIN, viewport),
+                KeyCode::End if is_control => state.hex_move(i64::MAX, viewport),
+                KeyCode::Home => state.hex_row_edge(false, viewport),
+                KeyCode::End => state.hex_row_edge(true, viewport),
+                KeyCode::Tab => state.hex_toggle_column(),
+                KeyCode::Char('z') if is_control => state.hex_undo(viewport),
+                KeyCode::Char(c) if !is_control => {
+                    if let Err(e) = state.hex_type(c, viewport) {
+                        app_state.display_error(e.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn save_editor(app_state: &mut AppState) {
+    if let Err(e) = app_state.editor_save() {
+        app_state.display_error(e);
+    }
+}
+
+fn handle_disk_usage_key(app_state: &mut AppState, key: KeyEvent) {
+    let page = app_state.page_size.max(1) as isize;
+    let Some(view) = &mut app_state.du_view else {
+        // Still scanning
+        if key.code == KeyCode::Esc || key.code == KeyCode::Char('q') {
+            app_state.close_disk_usage();
+        }
+        return;
+    };
+    if view.is_delete_prompt {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => app_state.du_delete_confirm(),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => view.is_delete_prompt = false,
+            _ => {}
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => app_state.close_disk_usage(),
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::ALT) => app_state.close_disk_usage(),
+        KeyCode::Up => view.select_by(-1),
+        KeyCode::Down => view.select_by(1),
+        KeyCode::PageUp => view.select_by(-page),
+        KeyCode::PageDown => view.select_by(page),
+        KeyCode::Home => view.selected = 0,
+        KeyCode::End => view.select_by(isize::MAX / 2),
+        KeyCode::Enter | KeyCode::Right => view.enter(),
+        KeyCode::Left | KeyCode::Backspace => view.leave(),
+        KeyCode::Char('a') => view.toggle_apparent(),
+        KeyCode::Char('r') => app_state.rescan_disk_usage(),
+        KeyCode::F(8) | KeyCode::Delete | KeyCode::Char('d') => view.is_delete_prompt = view.selected_row().is_some(),
+        _ => {}
+    }
+}
+
+fn handle_sync_key(app_state: &mut AppState, key: KeyEvent) {
+    if let Some(input) = &mut app_state.sync_export {
+        match key.code {
+            KeyCode::Esc => app_state.sync_export = None,
+            KeyCode::Enter => app_state.save_sync_export(),
+            KeyCode::Char(to_insert) => input.insert(to_insert),
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete_forward(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            _ => {}
+        }
+        return;
+    }
+    let page = app_state.page_size.max(1) as isize;
+    if let Some(plan) = &mut app_state.sync_plan {
+        // A running sync only listens for stop
+        if let Some(run) = &plan.run {
+            if key.code == KeyCode::Esc {
+                run.cancel();
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => app_state.stop_sync_plan(),
+            KeyCode::Enter => app_state.run_sync(),
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.open_sync_export(),
+            KeyCode::Up => plan.select_by(-1),
+            KeyCode::Down => plan.select_by(1),
+            KeyCode::PageUp => plan.select_by(-page),
+            KeyCode::PageDown => plan.select_by(page),
+            KeyCode::Home => plan.selected = 0,
+            KeyCode::End => plan.select_by(isize::MAX / 2),
+            KeyCode::Char(' ') | KeyCode::Insert => plan.toggle_selected(),
+            KeyCode::Char('*') => plan.invert_all(),
+            _ => {}
+        }
+        return;
+    }
+    let dialog = &mut app_state.sync_dialog;
+    match key.code {
+        KeyCode::Esc => app_state.reset_sync(),
+        KeyCode::Enter => app_state.start_sync_plan(),
+        KeyCode::Up | KeyCode::BackTab => dialog.focus_by(-1),
+        KeyCode::Down | KeyCode::Tab => dialog.focus_by(1),
+        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => dialog.toggle_focused(),
+        _ => {}
+    }
+}
+
+fn handle_compare_key(app_state: &mut AppState, key: KeyEvent) {
+    // A running comparison only listens for cancel
+    if app_state.dir_compare.is_some() {
+        if key.code == KeyCode::Esc {
+            app_state.dir_compare = None;
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Esc => app_state.reset_compare(),
+        KeyCode::Enter => app_state.start_compare(),
+        KeyCode::Up => app_state.compare_cursor = 0,
+        KeyCode::Down => app_state.compare_cursor = 1,
+        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => app_state.toggle_compare_option(),
+        _ => {}
+    }
+}
+
+fn handle_mounts_key(app_state: &mut AppState, key: KeyEvent) {
+    let page = app_state.page_size.max(1) as usize;
+    let last = app_state.mounts.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Esc => app_state.reset_mounts(),
+        KeyCode::Enter => app_state.go_to_mount(),
+        KeyCode::Down => app_state.mounts_selected = (app_state.mounts_selected + 1).min(last),
+        KeyCode::Up => app_state.mounts_selected = app_state.mounts_selected.saturating_sub(1),
+        KeyCode::PageDown => app_state.mounts_selected = (app_state.mounts_selected + page).min(last),
+        KeyCode::PageUp => app_state.mounts_selected = app_state.mounts_selected.saturating_sub(page),
+        KeyCode::Home => app_state.mounts_selected = 0,
+        KeyCode::End => app_state.mounts_selected = last,
+        _ => {}
+    }
+}
+
+fn handle_fuzzy_key(app_state: &mut AppState, key: KeyEvent) {
+    let page = app_state.page_size.max(1) as isize;
+    let Some(finder) = &mut app_state.fuzzy_finder else { return };
+    match key.code {
+        KeyCode::Esc => app_state.reset_fuzzy(),
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.reset_fuzzy(),
+        KeyCode::Enter => app_state.open_fuzzy_result(FuzzyAction::GoTo),
+        KeyCode::F(3) => app_state.open_fuzzy_result(FuzzyAction::View),
+        KeyCode::F(4) => app_state.open_fuzzy_result(FuzzyAction::Edit),
+        KeyCode::Down => finder.select_by(1),
+        KeyCode::Up => finder.select_by(-1),
+        KeyCode::PageDown => finder.select_by(page),
+        KeyCode::PageUp => finder.select_by(-page),
+        KeyCode::Left => finder.query.move_left(),
+        KeyCode::Right => finder.query.move_right(),
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            finder.query.insert(c);
+            finder.query_changed();
+        }
+        KeyCode::Backspace => {
+            finder.query.backspace();
+            finder.query_changed();
+        }
+        KeyCode::Delete => {
+            finder.query.delete_forward();
+            finder.query_changed();
+        }
+        _ => {}
+    }
+}
+
 fn toggle_help(app_state: &mut AppState) {
     if app_state.is_error_displayed {
         return;
@@ -293,7 +708,7 @@ fn toggle_rename(app_state: &mut AppState) {
         } else {
             &app_state.children_right[selected_index]
         };
-        app_state.rename_input.set(selected_item.name_full.clone());
+        app_state.rename_input.set(selected_item.file_name().to_string());
     } else {
         app_state.reset_rename();
     }
@@ -314,25 +729,15 @@ fn handle_rename(app_state: &mut AppState) {
     let selected_item = state.selected().and_then(|index| children.get(index).cloned());
 
     if let Some(item) = &selected_item {
-        let mut original_path = parent_path.clone();
-        original_path.push(item.name_full.clone());
-        let mut new_path = parent_path.clone();
-        new_path.push(app_state.rename_input.text.clone());
+        let original_path = item.path.clone();
+        // Tree and list rows can live in other directories, rename in place
+        let new_path = original_path.parent().unwrap_or(parent_path).join(name_from_display(&app_state.rename_input.text, item.os_name()));
 
-        match rename_path(original_path, new_path) {
+        match rename_path(original_path.clone(), new_path.clone()) {
             Ok(_) => {
+                app_state.rename_in_list(&original_path, &new_path);
                 // Only reload the active panel
-                let current_dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
-                match load_directory_rows( current_dir) {
-                    Ok(items) => {
-                        if app_state.is_left_active {
-                            app_state.children_left = items;
-                        } else {
-                            app_state.children_right = items;
-                        }
-                    }
-                    Err(e) => app_state.display_error(e.to_string()),
-                }
+                app_state.reload_panel(app_state.is_left_active);
             }
             Err(e) => app_state.display_error(e.to_string()),
         }
@@ -349,6 +754,13 @@ fn handle_esc(app_state: &mut AppState) {
     app_state.reset_delete();
     app_state.reset_copy();
     app_state.reset_move();
+    app_state.reset_filter();
+    app_state.reset_panelize();
+    app_state.reset_compare();
+    app_state.reset_sync();
+    app_state.reset_mounts();
+    app_state.reset_find();
+    app_state.reset_fuzzy();
     app_state.close_viewer();
     app_state.close_editor();
 }
@@ -357,6 +769,7 @@ fn handle_tab_switching(app_state: &mut AppState) {
     if app_state.is_error_displayed || app_state.is_f1_displayed {
         return;
     }
+    app_state.search_clear();
     app_state.is_left_active = !app_state.is_left_active
 }
 
@@ -380,6 +793,13 @@ fn handle_enter_directory(app_state: &mut AppState) {
     handle_panel_operation(app_state, enter_directory_panel)
 }
 
+/// Right in Miller columns enters a directory, like Enter, but never launches files.
+fn handle_miller_right(app_state: &mut AppState) {
+    if app_state.cursor_path().is_some_and(|path| path.is_dir()) {
+        handle_enter_directory(app_state);
+    }
+}
+
 fn handle_panel_operation(app_state: &mut AppState, operation: impl FnOnce(&mut AppState)) {
     if app_state.is_error_displayed || app_state.is_f1_displayed {
         return;
@@ -388,110 +808,36 @@ fn handle_panel_operation(app_state: &mut AppState, operation: impl FnOnce(&mut
 }
 
 fn navigate_up_panel(app_state: &mut AppState) {
-    let dir_new: std::path::PathBuf;
-    let name_current: String;
-
-    {
-        let dir = if app_state.is_left_active { &mut app_state.dir_left } else { &mut app_state.dir_right };
-        name_current = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
-        if let Some(parent) = dir.parent() {
-            dir_new = parent.to_path_buf();
-        } else {
-            return;
-        }
-    }
-
-    let result = load_directory_rows( &dir_new);
-    match result {
-        Ok(children_new) => {
-            let dir = if app_state.is_left_active { &mut app_state.dir_left } else { &mut app_state.dir_right };
-            let children = if app_state.is_left_active { &mut app_state.children_left } else { &mut app_state.children_right };
-            let state = if app_state.is_left_active { &mut app_state.state_left } else { &mut app_state.state_right };
-
-            *dir = dir_new;
-            *children = children_new;
-            let selected_new = children.iter().position(|item| item.name == name_current).unwrap_or(0);
-            state.select(Some(selected_new));
-            app_state.search_clear();
-            app_state.clear_active_selections();
-        }
-        Err(e) => app_state.display_error(e.to_string()),
+    if app_state.is_list_active() {
+        app_state.close_list();
+        return;
     }
+    let dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
+    let Some(parent) = dir.parent().map(|parent| parent.to_path_buf()) else { return };
+    // The cursor lands on the directory just left
+    let current = dir.clone();
+    app_state.open_dir(app_state.is_left_active, parent, Some(current));
 }
 
 fn enter_directory_panel(app_state: &mut AppState) {
-    let selected_item: Option<Item>;
-    let mut parent_dir_new: Option<std::path::PathBuf>;
-    let current_dir_name: Option<String>;
-    let mut enter_subdir: Option<std::path::PathBuf>;
-
-    {
+    let selected_item = {
         let state = if app_state.is_left_active { &app_state.state_left } else { &app_state.state_right };
         let children = if app_state.is_left_active { &app_state.children_left } else { &app_state.children_right };
-        let dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
-
-        selected_item = state.selected().and_then(|index| children.get(index).cloned());
-        parent_dir_new = None;
-        current_dir_name = dir.file_name().map(|n| n.to_string_lossy().to_string());
-        enter_subdir = None;
-
-        if let Some(item) = &selected_item {
-            if item.name == ".." {
-                if let Some(parent) = dir.parent() {
-                    parent_dir_new = Some(parent.to_path_buf());
-                }
-            } else if item.is_dir {
-                let mut dir_new = dir.clone();
-                dir_new.push(item.name.clone());
-                enter_subdir = Some(dir_new);
-            }
-        }
-    }
-
-    if let Some(dir_new) = parent_dir_new {
-        let result = load_directory_rows( &dir_new);
-        match result {
-            Ok(children_new) => {
-                let dir = if app_state.is_left_active { &mut app_state.dir_left } else { &mut app_state.dir_right };
-                let children_mut = if app_state.is_left_active { &mut app_state.children_left } else { &mut app_state.children_right };
-                let state = if app_state.is_left_active { &mut app_state.state_left } else { &mut app_state.state_right };
-
-                *dir = dir_new;
-                *children_mut = children_new;
-                let selected_new = children_mut.iter().position(|item| Some(&item.name) == current_dir_name.as_ref()).unwrap_or(0);
-                state.select(Some(selected_new));
-                app_state.search_clear();
-                app_state.clear_active_selections();
-            }
-            Err(e) => app_state.display_error(e.to_string()),
-        }
-    } else if let Some(item) = &selected_item {
-        if !item.is_dir {
-            let dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
-            let file_path = dir.join(&item.name_full);
-            if let Err(e) = open_with_default(&file_path) {
-                app_state.display_error(format!("Cannot open file: {}", e));
-            }
-            return;
-        }
-    }
+        state.selected().and_then(|index| children.get(index).cloned())
+    };
+    let Some(item) = selected_item else { return };
 
-    if let Some(dir_new) = enter_subdir {
-        let result = load_directory_rows( &dir_new);
-        match result {
-            Ok(children_new) => {
-                let dir = if app_state.is_left_active { &mut app_state.dir_left } else { &mut app_state.dir_right };
-                let children = if app_state.is_left_active { &mut app_state.children_left } else { &mut app_state.children_right };
-                let state = if app_state.is_left_active { &mut app_state.state_left } else { &mut app_state.state_right };
-
-                *dir = dir_new;
-                *children = children_new;
-                state.select(Some(0));
-                app_state.search_clear();
-                app_state.clear_active_selections();
-            }
-            Err(e) => app_state.display_error(e.to_string()),
+    if item.name == ".." {
+        // ".." of a panelized list goes back to the panel's directory
+        if app_state.is_list_active() {
+            app_state.close_list();
+        } else {
+            navigate_up_panel(app_state);
         }
+    } else if item.is_dir {
+        app_state.open_dir(app_state.is_left_active, item.path, None);
+    } else if let Err(e) = open_with_default(&item.path) {
+        app_state.display_error(format!("Cannot open file: {}", e));
     }
 }
 
@@ -532,11 +878,11 @@ fn toggle_delete(app_state: &mut AppState) {
         let children = if app_state.is_left_active { &app_state.children_left } else { &app_state.children_right };
         let selected_set = if app_state.is_left_active { &app_state.selected_left } else { &app_state.selected_right };
 
-        let items: Vec<(String, bool)> = if !selected_set.is_empty() {
+        let items: Vec<(PathBuf, bool)> = if !selected_set.is_empty() {
             selected_set.iter()
                 .filter_map(|&idx| children.get(idx))
                 .filter(|item| item.name != "..")
-                .map(|item| (item.name_full.clone(), item.is_dir))
+                .map(|item| (item.path.clone(), item.is_dir))
                 .collect()
         } else {
             let selected_index = if app_state.is_left_active { app_state.state_left.selected().unwrap_or(0) } else { app_state.state_right.selected().unwrap_or(0) };
@@ -546,7 +892,7 @@ fn toggle_delete(app_state: &mut AppState) {
                     app_state.is_f8_displayed = false;
                     return;
                 }
-                vec![(item.name_full.clone(), item.is_dir)]
+                vec![(item.path.clone(), item.is_dir)]
             } else {
                 app_state.is_f8_displayed = false;
                 return;
@@ -565,12 +911,10 @@ fn toggle_delete(app_state: &mut AppState) {
 }
 
 fn handle_delete_confirm(app_state: &mut AppState) {
-    let parent_path = if app_state.is_left_active { app_state.dir_left.clone() } else { app_state.dir_right.clone() };
     let items = std::mem::take(&mut app_state.delete_items);
 
-    for (name, is_dir) in &items {
-        let item_path = parent_path.join(name);
-        if let Err(e) = delete_path(item_path, *is_dir) {
+    for (item_path, is_dir) in &items {
+        if let Err(e) = delete_path(item_path.clone(), *is_dir) {
             app_state.display_error(e.to_string());
             app_state.reset_delete();
             return;
@@ -578,28 +922,7 @@ fn handle_delete_confirm(app_state: &mut AppState) {
     }
 
     // Reload the directory
-    match load_directory_rows(&parent_path) {
-        Ok(new_items) => {
-            if app_state.is_left_active {
-                app_state.children_left = new_items;
-                let len = app_state.children_left.len();
-                if let Some(selected) = app_state.state_left.selected() {
-                    if selected >= len {
-                        app_state.state_left.select(Some(len.saturating_sub(1)));
-                    }
-                }
-            } else {
-                app_state.children_right = new_items;
-                let len = app_state.children_right.len();
-                if let Some(selected) = app_state.state_right.selected() {
-                    if selected >= len {
-                        app_state.state_right.select(Some(len.saturating_sub(1)));
-                    }
-                }
-            }
-        }
-        Err(e) => app_state.display_error(e.to_string()),
-    }
+    app_state.reload_panel(app_state.is_left_active);
 
     app_state.clear_active_selections();
     app_state.reset_delete();
@@ -616,29 +939,9 @@ fn handle_create_confirm(app_state: &mut AppState) {
     let mut new_dir_path = parent_path.clone();
     new_dir_path.push(&app_state.create_input.text);
 
-    match create_directory(new_dir_path) {
-        Ok(_) => {
-            // Reload the directory
-            let current_dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
-
-            match load_directory_rows(current_dir) {
-                Ok(items) => {
-                    if app_state.is_left_active {
-                        app_state.children_left = items;
-                        // Select the newly created directory
-                        if let Some(index) = app_state.children_left.iter().position(|item| item.name == app_state.create_input.text) {
-                            app_state.state_left.select(Some(index));
-                        }
-                    } else {
-                        app_state.children_right = items;
-                        if let Some(index) = app_state.children_right.iter().position(|item| item.name == app_state.create_input.text) {
-                            app_state.state_right.select(Some(index));
-                        }
-                    }
-                }
-                Err(e) => app_state.display_error(e.to_string()),
-            }
-        }
+    match create_directory(new_dir_path.clone()) {
+        // Reload the directory with the cursor on the new one
+        Ok(_) => app_state.reload_panel_selecting(app_state.is_left_active, Some(new_dir_path)),
         Err(e) => app_state.display_error(e.to_string()),
     }
 
@@ -669,14 +972,7 @@ fn handle_f3_view(app_state: &mut AppState) {
             return;
         }
 
-        // Build file path
-        let parent_path = if app_state.is_left_active {
-            &app_state.dir_left
-        } else {
-            &app_state.dir_right
-        };
-        let mut file_path = parent_path.clone();
-        file_path.push(&item.name_full);
+        let file_path = item.path.clone();
 
         // Open viewer
         if let Err(e) = app_state.open_viewer(file_path) {
@@ -709,14 +1005,7 @@ fn handle_f4_edit(app_state: &mut AppState) {
             return;
         }
 
-        // Build file path
-        let parent_path = if app_state.is_left_active {
-            &app_state.dir_left
-        } else {
-            &app_state.dir_right
-        };
-        let mut file_path = parent_path.clone();
-        file_path.push(&item.name_full);
+        let file_path = item.path.clone();
 
         // Open internal editor
         if let Err(e) = app_state.open_editor(file_path) {
@@ -796,14 +1085,13 @@ fn toggle_copy(app_state: &mut AppState) {
     if app_state.is_f5_displayed {
         let children = if app_state.is_left_active { &app_state.children_left } else { &app_state.children_right };
         let selected_set = if app_state.is_left_active { &app_state.selected_left } else { &app_state.selected_right };
-        let source_dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
         let dest_dir = if app_state.is_left_active { &app_state.dir_right } else { &app_state.dir_left };
 
-        let items: Vec<(PathBuf, PathBuf, bool)> = if !selected_set.is_empty() {
+        let mut items: Vec<(PathBuf, PathBuf, bool)> = if !selected_set.is_empty() {
             selected_set.iter()
                 .filter_map(|&idx| children.get(idx))
                 .filter(|item| item.name != "..")
-                .map(|item| (source_dir.join(&item.name_full), dest_dir.join(&item.name_full), item.is_dir))
+                .map(|item| (item.path.clone(), dest_dir.join(item.os_name()), item.is_dir))
                 .collect()
         } else {
             let selected_index = if app_state.is_left_active { app_state.state_left.selected().unwrap_or(0) } else { app_state.state_right.selected().unwrap_or(0) };
@@ -813,7 +1101,7 @@ fn toggle_copy(app_state: &mut AppState) {
                     app_state.is_f5_displayed = false;
                     return;
                 }
-                vec![(source_dir.join(&item.name_full), dest_dir.join(&item.name_full), item.is_dir)]
+                vec![(item.path.clone(), dest_dir.join(item.os_name()), item.is_dir)]
             } else {
                 app_state.is_f5_displayed = false;
                 return;
@@ -825,6 +1113,29 @@ fn toggle_copy(app_state: &mut AppState) {
             return;
         }
 
+        // Only a copy of compare-marked entries overwrites, after the dialog warns about it; a directory with
+        // differences inside brings across just the flagged entries below it
+        let compare_flags = if app_state.is_left_active { &app_state.compare_left } else { &app_state.compare_right };
+        app_state.is_compare_copy = items.iter().all(|(source, _, _)| compare_flags.contains_key(source));
+        if app_state.is_compare_copy {
+            let items_before = std::mem::take(&mut items);
+            for (source, dest, is_dir) in items_before {
+                if compare_flags.get(&source) != Some(&CompareFlag::Inside) {
+                    items.push((source, dest, is_dir));
+                    continue;
+                }
+                for (entry, entry_is_dir) in entries_to_copy(compare_flags, &source) {
+                    let entry_dest = entry.strip_prefix(&source).map(|relative| dest.join(relative)).unwrap_or_else(|_| dest.clone());
+                    items.push((entry, entry_dest, entry_is_dir));
+                }
+            }
+            if items.is_empty() {
+                app_state.is_f5_displayed = false;
+                app_state.is_compare_copy = false;
+                return;
+            }
+            app_state.copy_overwrites = items.iter().filter(|(_, dest, _)| dest.symlink_metadata().is_ok()).count();
+        }
         app_state.copy_items = items;
     } else {
         app_state.reset_copy();
@@ -835,12 +1146,12 @@ fn handle_copy_confirm(app_state: &mut AppState) {
     let items = std::mem::take(&mut app_state.copy_items);
 
     for (source, dest, is_dir) in &items {
-        if dest.exists() {
-            app_state.display_error(format!("Destination already exists: {}", dest.display()));
+        if !app_state.is_compare_copy && dest.symlink_metadata().is_ok() {
+            app_state.display_error(format!("Destination already exists: {}", display_name(dest.as_os_str())));
             app_state.reset_copy();
             return;
         }
-        if let Err(e) = copy_path(source.clone(), dest.clone(), *is_dir) {
+        if let Err(e) = copy_path(source.clone(), dest.clone(), *is_dir, app_state.copy_devices) {
             app_state.display_error(e.to_string());
             app_state.reset_copy();
             return;
@@ -848,17 +1159,7 @@ fn handle_copy_confirm(app_state: &mut AppState) {
     }
 
     // Reload the destination panel (opposite of active)
-    let dest_dir = if app_state.is_left_active { app_state.dir_right.clone() } else { app_state.dir_left.clone() };
-    match load_directory_rows(&dest_dir) {
-        Ok(new_items) => {
-            if app_state.is_left_active {
-                app_state.children_right = new_items;
-            } else {
-                app_state.children_left = new_items;
-            }
-        }
-        Err(e) => app_state.display_error(e.to_string()),
-    }
+    app_state.reload_panel(!app_state.is_left_active);
 
     app_state.clear_active_selections();
     app_state.reset_copy();
@@ -874,14 +1175,13 @@ fn toggle_move(app_state: &mut AppState) {
     if app_state.is_f6_displayed {
         let children = if app_state.is_left_active { &app_state.children_left } else { &app_state.children_right };
         let selected_set = if app_state.is_left_active { &app_state.selected_left } else { &app_state.selected_right };
-        let source_dir = if app_state.is_left_active { &app_state.dir_left } else { &app_state.dir_right };
         let dest_dir = if app_state.is_left_active { &app_state.dir_right } else { &app_state.dir_left };
 
         let items: Vec<(PathBuf, PathBuf, bool)> = if !selected_set.is_empty() {
             selected_set.iter()
                 .filter_map(|&idx| children.get(idx))
                 .filter(|item| item.name != "..")
-                .map(|item| (source_dir.join(&item.name_full), dest_dir.join(&item.name_full), item.is_dir))
+                .map(|item| (item.path.clone(), dest_dir.join(item.os_name()), item.is_dir))
                 .collect()
         } else {
             let selected_index = if app_state.is_left_active { app_state.state_left.selected().unwrap_or(0) } else { app_state.state_right.selected().unwrap_or(0) };
@@ -891,7 +1191,7 @@ fn toggle_move(app_state: &mut AppState) {
                     app_state.is_f6_displayed = false;
                     return;
                 }
-                vec![(source_dir.join(&item.name_full), dest_dir.join(&item.name_full), item.is_dir)]
+                vec![(item.path.clone(), dest_dir.join(item.os_name()), item.is_dir)]
             } else {
                 app_state.is_f6_displayed = false;
                 return;
@@ -911,16 +1211,15 @@ fn toggle_move(app_state: &mut AppState) {
 
 fn handle_move_confirm(app_state: &mut AppState) {
     let items = std::mem::take(&mut app_state.move_items);
-    let source_dir = if app_state.is_left_active { app_state.dir_left.clone() } else { app_state.dir_right.clone() };
-    let dest_dir = if app_state.is_left_active { app_state.dir_right.clone() } else { app_state.dir_left.clone() };
 
     for (source, dest, is_dir) in &items {
-        if dest.exists() {
-            app_state.display_error(format!("Destination already exists: {}", dest.display()));
+        // A dangling symlink is still in the way
+        if dest.symlink_metadata().is_ok() {
+            app_state.display_error(format!("Destination already exists: {}", display_name(dest.as_os_str())));
             app_state.reset_move();
             return;
         }
-        if let Err(e) = move_path(source.clone(), dest.clone(), *is_dir) {
+        if let Err(e) = move_path(source.clone(), dest.clone(), *is_dir, app_state.copy_devices) {
             app_state.display_error(e.to_string());
             app_state.reset_move();
             return;
@@ -928,40 +1227,10 @@ fn handle_move_confirm(app_state: &mut AppState) {
     }
 
     // Reload source panel
-    match load_directory_rows(&source_dir) {
-        Ok(new_items) => {
-            if app_state.is_left_active {
-                app_state.children_left = new_items;
-                let len = app_state.children_left.len();
-                if let Some(selected) = app_state.state_left.selected() {
-                    if selected >= len {
-                        app_state.state_left.select(Some(len.saturating_sub(1)));
-                    }
-                }
-            } else {
-                app_state.children_right = new_items;
-                let len = app_state.children_right.len();
-                if let Some(selected) = app_state.state_right.selected() {
-                    if selected >= len {
-                        app_state.state_right.select(Some(len.saturating_sub(1)));
-                    }
-                }
-            }
-        }
-        Err(e) => app_state.display_error(e.to_string()),
-    }
+    app_state.reload_panel(app_state.is_left_active);
 
     // Reload destination panel
-    match load_directory_rows(&dest_dir) {
-        Ok(new_items) => {
-            if app_state.is_left_active {
-                app_state.children_right = new_items;
-            } else {
-                app_state.children_left = new_items;
-            }
-        }
-        Err(e) => app_state.display_error(e.to_string()),
-    }
+    app_state.reload_panel(!app_state.is_left_active);
 
     app_state.clear_active_selections();
     app_state.reset_move();
@@ -977,6 +1246,15 @@ fn handle_mouse_click(app_state: &mut AppState, column: u16, row: u16) {
         || app_state.is_f6_displayed
         || app_state.is_f7_displayed
         || app_state.is_f8_displayed
+        || app_state.is_filter_displayed
+        || app_state.is_panelize_displayed
+        || app_state.is_compare_displayed
+        || app_state.is_sync_displayed
+        || app_state.is_du_displayed
+        || app_state.is_mounts_displayed
+        || app_state.is_find_displayed
+        || app_state.is_fuzzy_displayed
+        || app_state.is_columns_displayed
     {
         return;
     }
@@ -1002,34 +1280,43 @@ fn handle_mouse_click(app_state: &mut AppState, column: u16, row: u16) {
     // Clear all selections on mouse click
     app_state.clear_all_selections();
 
-    // Get terminal size
-    let (term_width, term_height) = crossterm::terminal::size().unwrap_or((80, 24));
-
-    // Layout: top panel (3) + path bar (1) + file tables + bottom panel (1) + f-key bar (3)
-    // File tables start at row 4, with header row at 4, data starts at row 5
-    let table_start_row = 4u16;
-    let table_end_row = term_height.saturating_sub(4); // Bottom panel (1) + f-key bar (3)
-
-    // Check if click is within file table area
-    if row < table_start_row || row >= table_end_row {
+    // Determine which panel was clicked and where its table starts; in Miller columns only the middle column is laid out
+    let position = Position::new(column, row);
+    let layout = app_state.panel_layout;
+    let (clicked_left, panel_area) = if layout.left.contains(position) {
+        (true, layout.left)
+    } else if layout.right.contains(position) {
+        (false, layout.right)
+    } else {
         return;
-    }
+    };
 
     // Calculate which row in the table was clicked (accounting for header)
-    let header_row = table_start_row;
+    let header_row = panel_area.y;
+    if row >= panel_area.bottom() {
+        return;
+    }
     if row <= header_row {
         return; // Clicked on header
     }
 
     let clicked_table_row = (row - header_row - 1) as usize;
+    // The quick view panel only shows the preview
+    if app_state.is_quick_view && clicked_left != app_state.is_left_active {
+        return;
+    }
 
-    // Determine which panel was clicked (left half or right half)
-    let panel_width = term_width / 2;
-    let clicked_left = column < panel_width;
-
-    // Set active panel
+    // Set active panel; a search belongs to the panel it was typed in
+    if app_state.is_left_active != clicked_left {
+        app_state.search_clear();
+    }
     app_state.is_left_active = clicked_left;
 
+    // Brief mode flows rows through columns; a click only activates the panel
+    if app_state.is_brief_active() {
+        return;
+    }
+
     // Get the viewport offset for the clicked panel to calculate actual index
     let children = if clicked_left {
         &app_state.children_left
@@ -1043,7 +1330,7 @@ fn handle_mouse_click(app_state: &mut AppState, column: u16, row: u16) {
     }
 
     // Calculate viewport offset (same logic as in ui.rs build_viewport_rows)
-    let viewport_height = (table_end_row - table_start_row - 1) as usize;
+    let viewport_height = panel_area.height.saturating_sub(1) as usize;
     let state = if clicked_left {
         &app_state.state_left
     } else {
@@ -1083,9 +1370,7 @@ fn handle_mouse_click(app_state: &mut AppState, column: u16, row: u16) {
                 if children[actual_index].is_dir {
                     enter_directory_panel(app_state);
                 } else {
-                    let dir = if clicked_left { &app_state.dir_left } else { &app_state.dir_right };
-                    let file_path = dir.join(&children[actual_index].name_full);
-                    if let Err(e) = open_with_default(&file_path) {
+                    if let Err(e) = open_with_default(&children[actual_index].path) {
                         app_state.display_error(format!("Cannot open file: {}", e));
                     }
                 }
@@ -1094,6 +1379,12 @@ fn handle_mouse_click(app_state: &mut AppState, column: u16, row: u16) {
     }
 }
 
+/// The separator between the panels can be dragged to resize them.
+fn is_on_split_separator(app_state: &AppState, column: u16, row: u16) -> bool {
+    let is_modal = app_state.is_error_displayed || app_state.is_f1_displayed || app_state.is_f3_displayed || app_state.is_f5_displayed || app_state.is_f6_displayed || app_state.is_f7_displayed || app_state.is_f8_displayed || app_state.is_filter_displayed || app_state.is_panelize_displayed || app_state.is_compare_displayed || app_state.is_sync_displayed || app_state.is_du_displayed || app_state.is_mounts_displayed || app_state.is_find_displayed || app_state.is_fuzzy_displayed || app_state.is_columns_displayed;
+    !is_modal && !app_state.is_miller && !app_state.is_zoomed && app_state.panel_layout.separator.contains(Position::new(column, row))
+}
+
 fn handle_editor_click(app_state: &mut AppState, column: u16, row: u16) {
     let (term_width, term_height) = crossterm::terminal::size().unwrap_or((80, 24));
 
@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Every this many lines the index keeps the byte offset where the line starts.
+pub const CHECKPOINT_LINES: usize = 1024;
+/// Longer lines are cut when shown, so a file without newlines cannot fill memory.
+pub const MAX_LINE_BYTES: usize = 64 * 1024;
+const CHUNK_SIZE: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+enum IndexMessage {
+    /// New checkpoints plus newlines counted and bytes read so far.
+    Progress(Vec<u64>, usize, u64),
+    Failed(String),
+    Done,
+}
+
+/// Sparse line index of a file, built in a background thread so the viewer can open any file at once.
+/// Memory is one offset per `CHECKPOINT_LINES` lines, whatever the file size.
+pub struct LineIndex {
+    /// Byte offset of line `i * CHECKPOINT_LINES` at index `i`.
+    pub checkpoints: Vec<u64>,
+    /// Newlines found so far; the file has one line more once indexing is done.
+    pub newlines: usize,
+    /// Bytes read so far.
+    pub scanned: u64,
+    pub done: bool,
+    receiver: Receiver<IndexMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl LineIndex {
+    pub fn start(path: PathBuf) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let mut file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    let _ = sender.send(IndexMessage::Failed(e.to_string()));
+                    return;
+                }
+            };
+            let mut buffer = vec![0; CHUNK_SIZE];
+            let mut batch = Vec::new();
+            let (mut newlines, mut scanned, mut sent_at) = (0, 0u64, Instant::now());
+            loop {
+                if thread_cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                let read = match file.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) => {
+                        let _ = sender.send(IndexMessage::Failed(e.to_string()));
+                        return;
+                    }
+                };
+                for (position, _) in buffer[..read].iter().enumerate().filter(|(_, byte)| **byte == b'\n') {
+                    newlines += 1;
+                    if newlines % CHECKPOINT_LINES == 0 {
+                        batch.push(scanned + position as u64 + 1);
+                    }
+                }
+                scanned += read as u64;
+                if sent_at.elapsed() >= PROGRESS_INTERVAL {
+                    if sender.send(IndexMessage::Progress(std::mem::take(&mut batch), newlines, scanned)).is_err() {
+                        return;
+                    }
+                    sent_at = Instant::now();
+                }
+            }
+            let _ = sender.send(IndexMessage::Progress(batch, newlines, scanned));
+            let _ = sender.send(IndexMessage::Done);
+        });
+
+        Self { checkpoints: vec![0], newlines: 0, scanned: 0, done: false, receiver, cancel }
+    }
+
+    /// Takes in progress since the last call; the error if reading the file failed.
+    pub fn poll(&mut self) -> Option<String> {
+        let mut error = None;
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                IndexMessage::Progress(checkpoints, newlines, scanned) => {
+                    self.checkpoints.extend(checkpoints);
+                    self.newlines = newlines;
+                    self.scanned = scanned;
+                }
+                IndexMessage::Failed(e) => {
+                    // What was indexed stays usable
+                    self.done = true;
+                    error = Some(e);
+                }
+                IndexMessage::Done => self.done = true,
+            }
+        }
+        error
+    }
+
+    /// Lines indexed so far, the line still being read included.
+    pub fn lines(&self) -> usize {
+        self.newlines + 1
+    }
+
+    /// Line count extrapolated from the part already read, until indexing is done.
+    pub fn estimate(&self, file_size: u64) -> Option<usize> {
+        if self.done || self.scanned == 0 {
+            return None;
+        }
+        Some((self.lines() as u128 * file_size as u128 / self.scanned as u128) as usize)
+    }
+
+    /// Reads up to `count` lines from line `start`, starting at the nearest checkpoint before it.
+    /// Past the end fewer lines come back; with a trailing newline the last line is empty.
+    /// While indexing, lines from `start` past the indexed part come back empty instead of being scanned for.
+    pub fn read_lines(&self, file: &mut File, start: usize, count: usize) -> Result<Vec<String>, Error> {
+        if !self.done && start >= self.lines() {
+            return Ok(Vec::new());
+        }
+        let checkpoint = (start / CHECKPOINT_LINES).min(self.checkpoints.len() - 1);
+        file.seek(SeekFrom::Start(self.checkpoints[checkpoint]))?;
+        let mut reader = BufReader::new(file);
+        let skip = start - checkpoint * CHECKPOINT_LINES;
+        let mut lines = Vec::with_capacity(count);
+        let mut line = Vec::new();
+        for number in 0..skip + count {
+            line.clear();
+            let keep = number >= skip;
+            let has_newline = read_line_capped(&mut reader, &mut line, if keep { MAX_LINE_BYTES } else { 0 })?;
+            if keep {
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                lines.push(String::from_utf8_lossy(&line).into_owned());
+            }
+            if !has_newline {
+                break;
+            }
+        }
+        Ok(lines)
+    }
+}
+
+impl Drop for LineIndex {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Reads through the next newline, keeping at most `limit` bytes of the line (without the newline).
+/// False when the end of the file came before a newline.
+pub fn read_line_capped(reader: &mut impl BufRead, line: &mut Vec<u8>, limit: usize) -> Result<bool, Error> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+        let newline = buffer.iter().position(|&byte| byte == b'\n');
+        let chunk = &buffer[..newline.unwrap_or(buffer.len())];
+        let room = limit.saturating_sub(line.len());
+        line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        let used = chunk.len() + newline.map_or(0, |_| 1);
+        reader.consume(used);
+        if newline.is_some() {
+            return Ok(true);
+        }
+    }
+}
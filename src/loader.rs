@@ -0,0 +1,211 @@
+use crate::app::Item;
+use crate::fs_ops::{PanelFilter, entry_row, load_tree_rows, parent_item};
+use std::collections::HashSet;
+use std::fs::read_dir;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Listings that finish within this long are shown at once, without the loading state.
+pub const INSTANT_LOAD: Duration = Duration::from_millis(150);
+const BATCH_SIZE: usize = 1024;
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+/// Listings kept for going back to recently visited directories.
+const CACHE_CAPACITY: usize = 16;
+
+enum LoadMessage {
+    /// New rows plus the number of entries read so far, before filtering.
+    Rows(Vec<Item>, usize),
+    /// Reading stopped at an entry that could not be read.
+    Failed(String),
+    /// The directory could not be opened at all.
+    OpenFailed(Error),
+    Done,
+}
+
+/// A directory listing streaming in from a background thread, in directory order until it is complete.
+pub struct DirLoad {
+    /// Entries read so far, before filtering.
+    pub entries: usize,
+    pub done: bool,
+    /// Entry to put the cursor on once it arrives, e.g. the directory just left.
+    pub select: Option<PathBuf>,
+    /// Reloading the directory already shown: the old rows stay until the new listing is complete.
+    pub is_refresh: bool,
+    /// Rows of a refresh, held back until it is complete.
+    pub rows: Vec<Item>,
+    /// Why the listing ended early; the rows read until then are kept.
+    pub error: Option<String>,
+    /// Why the directory could not be opened; the load is done without rows.
+    pub open_error: Option<Error>,
+    /// A tree listing, complete and in tree order when it arrives, so it is never sorted.
+    pub is_tree: bool,
+    receiver: Receiver<LoadMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl DirLoad {
+    /// Reads `dir` in the background. Opening it happens there too, so a slow or hung filesystem never
+    /// blocks the caller; a missing or unreadable directory ends the load with `open_error`.
+    pub fn start(dir: PathBuf, filter: PanelFilter) -> Self {
+        Self::spawn(move |sender, cancel| read_listing(&dir, &filter, sender, cancel))
+    }
+
+    /// A load that replaces the panel's rows only once complete.
+    pub fn refresh(dir: PathBuf, filter: PanelFilter) -> Self {
+        let mut load = Self::start(dir, filter);
+        load.is_refresh = true;
+        load
+    }
+
+    /// Tree listing of `dir` with every directory in `expanded` read below it, sent in one piece.
+    pub fn start_tree(dir: PathBuf, filter: PanelFilter, expanded: HashSet<PathBuf>, is_refresh: bool) -> Self {
+        let mut load = Self::spawn(move |sender, _| {
+            match load_tree_rows(&dir, &filter, &expanded) {
+                Ok((items, total, error)) => {
+                    let _ = sender.send(LoadMessage::Rows(items, total));
+                    if let Some(error) = error {
+                        let _ = sender.send(LoadMessage::Failed(error));
+                    }
+                    let _ = sender.send(LoadMessage::Done);
+                }
+                Err(e) => {
+                    let _ = sender.send(LoadMessage::OpenFailed(e));
+                }
+            }
+        });
+        load.is_tree = true;
+        load.is_refresh = is_refresh;
+        load
+    }
+
+    fn spawn(read: impl FnOnce(&Sender<LoadMessage>, &AtomicBool) + Send + 'static) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || read(&sender, &thread_cancel));
+
+        Self { entries: 0, done: false, select: None, is_refresh: false, rows: Vec::new(), error: None, open_error: None, is_tree: false, receiver, cancel }
+    }
+
+    /// Rows received since the last call.
+    pub fn poll(&mut self) -> Vec<Item> {
+        let mut rows = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            self.receive(message, &mut rows);
+        }
+        rows
+    }
+
+    /// Like `poll`, but waits up to `timeout` for the listing to complete.
+    pub fn wait(&mut self, timeout: Duration) -> Vec<Item> {
+        let deadline = Instant::now() + timeout;
+        let mut rows = Vec::new();
+        while !self.done {
+            match self.receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(message) => self.receive(message, &mut rows),
+                Err(_) => break,
+            }
+        }
+        rows
+    }
+
+    fn receive(&mut self, message: LoadMessage, rows: &mut Vec<Item>) {
+        match message {
+            LoadMessage::Rows(batch, entries) => {
+                rows.extend(batch);
+                self.entries = entries;
+            }
+            LoadMessage::Failed(error) => self.error = Some(error),
+            LoadMessage::OpenFailed(error) => {
+                self.open_error = Some(error);
+                self.done = true;
+            }
+            LoadMessage::Done => self.done = true,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for DirLoad {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Streams the rows of `dir` in batches, ".." first.
+fn read_listing(dir: &Path, filter: &PanelFilter, sender: &Sender<LoadMessage>, cancel: &AtomicBool) {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = sender.send(LoadMessage::OpenFailed(e));
+            return;
+        }
+    };
+    // Don't add ".." on root folder.
+    let mut batch: Vec<Item> = dir.parent().map(|parent| parent_item(parent.to_path_buf())).into_iter().collect();
+    let (mut read, mut sent_at) = (0, Instant::now());
+    for entry in entries {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let _ = sender.send(LoadMessage::Failed(e.to_string()));
+                break;
+            }
+        };
+        read += 1;
+        batch.extend(entry_row(&entry, filter));
+        if batch.len() >= BATCH_SIZE || sent_at.elapsed() >= BATCH_INTERVAL {
+            if sender.send(LoadMessage::Rows(std::mem::take(&mut batch), read)).is_err() {
+                return;
+            }
+            sent_at = Instant::now();
+        }
+    }
+    let _ = sender.send(LoadMessage::Rows(batch, read));
+    let _ = sender.send(LoadMessage::Done);
+}
+
+struct CachedListing {
+    dir: PathBuf,
+    filter: PanelFilter,
+    rows: Vec<Item>,
+    total: usize,
+}
+
+/// Recently read listings (sorted, filtered), least recently used first.
+pub struct ListingCache {
+    listings: Vec<CachedListing>,
+}
+
+impl ListingCache {
+    pub fn new() -> Self {
+        Self { listings: Vec::new() }
+    }
+
+    /// Cached rows and unfiltered count of `dir`, moved out of the cache: they are only a stand-in
+    /// while the directory is read again, and the fresh listing takes their place once complete.
+    pub fn take(&mut self, dir: &Path, filter: &PanelFilter) -> Option<(Vec<Item>, usize)> {
+        let index = self.listings.iter().position(|listing| listing.dir == dir && listing.filter == *filter)?;
+        let listing = self.listings.remove(index);
+        Some((listing.rows, listing.total))
+    }
+
+    pub fn insert(&mut self, dir: PathBuf, filter: PanelFilter, rows: Vec<Item>, total: usize) {
+        self.listings.retain(|listing| listing.dir != dir || listing.filter != filter);
+        if self.listings.len() >= CACHE_CAPACITY {
+            self.listings.remove(0);
+        }
+        self.listings.push(CachedListing { dir, filter, rows, total });
+    }
+}
@@ -1,10 +1,26 @@
 mod app;
+mod archive;
+mod columns;
+mod compare;
 mod constants;
+mod diskusage;
+mod find;
 mod fs_ops;
+mod fuzzy;
+mod gitignore;
 mod input;
+mod lineindex;
+mod loader;
+mod mounts;
+mod panelize;
+mod preview;
+mod quickview;
+mod settings;
+mod sync;
 mod ui;
 mod utils;
 mod viewer;
+mod watch;
 
 use app::AppState;
 use crossterm::{
@@ -12,7 +28,6 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use fs_ops::load_directory_rows;
 use input::handle_input;
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io::{Result, stdout};
@@ -27,16 +42,11 @@ fn main() -> Result<()> {
 
     let mut app_state = AppState::new();
 
-    match load_directory_rows(&app_state.dir_left) {
-        Ok(items) => app_state.children_left = items,
-        Err(e) => app_state.display_error(e.to_string()),
-    }
-    match load_directory_rows(&app_state.dir_right) {
-        Ok(items) => app_state.children_right = items,
-        Err(e) => app_state.display_error(e.to_string()),
-    }
+    app_state.open_dir(true, app_state.dir_left.clone(), None);
+    app_state.open_dir(false, app_state.dir_right.clone(), None);
 
     loop {
+        app_state.poll_background();
         render_ui(&mut terminal, &mut app_state);
         if !handle_input(&mut app_state)? {
             break;
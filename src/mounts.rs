@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Free space is re-read at most this often for an unchanged directory.
+const SPACE_REFRESH: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy)]
+pub struct SpaceInfo {
+    pub total: u64,
+    /// Available to unprivileged users (excludes the root reserve).
+    pub free: u64,
+}
+
+/// Size and free space of the filesystem holding `path`.
+#[cfg(unix)]
+pub fn space_info(path: &Path) -> Option<SpaceInfo> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs is plain old data, all zeroes is a valid value
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and stat is a valid, writable statvfs
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // Field widths differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let (block, blocks, available) = (stat.f_frsize as u64, stat.f_blocks as u64, stat.f_bavail as u64);
+    Some(SpaceInfo { total: blocks * block, free: available * block })
+}
+
+#[cfg(not(unix))]
+pub fn space_info(_path: &Path) -> Option<SpaceInfo> {
+    None
+}
+
+/// Free space of a panel's filesystem for the status bar, refreshed when the
+/// directory changes or every few seconds (copies elsewhere change it too).
+/// statvfs runs in a background thread, as it can hang on a dead network mount;
+/// the last known value stays shown until it answers.
+pub struct SpaceWatch {
+    dir: PathBuf,
+    checked_at: Option<Instant>,
+    pub info: Option<SpaceInfo>,
+    pending: Option<Receiver<Option<SpaceInfo>>>,
+}
+
+impl SpaceWatch {
+    pub fn new() -> Self {
+        Self { dir: PathBuf::new(), checked_at: None, info: None, pending: None }
+    }
+
+    pub fn refresh(&mut self, dir: &Path) {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(info) => {
+                    self.info = info;
+                    self.pending = None;
+                }
+                Err(TryRecvError::Empty) if self.dir == dir => return,
+                // Another directory now, or the thread is gone: ask again
+                Err(_) => self.pending = None,
+            }
+        }
+        if self.dir == dir && self.checked_at.is_some_and(|checked_at| checked_at.elapsed() < SPACE_REFRESH) {
+            return;
+        }
+        self.dir = dir.to_path_buf();
+        self.checked_at = Some(Instant::now());
+        let (sender, receiver) = channel();
+        let dir = dir.to_path_buf();
+        thread::spawn(move || {
+            let _ = sender.send(space_info(&dir));
+        });
+        self.pending = Some(receiver);
+    }
+}
+
+pub struct Mount {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    /// Device or remote the filesystem comes from.
+    pub source: String,
+    /// Unknown until `MountSpaces` reports it.
+    pub space: Option<SpaceInfo>,
+}
+
+/// Mounted filesystems from `/proc/self/mountinfo`, sizes still unknown.
+pub fn list_mounts() -> Result<Vec<Mount>, String> {
+    let mut mounts: Vec<Mount> = Vec::new();
+    for (mount_point, fs_type, source) in mount_table()? {
+        // A later mount on the same point hides the earlier one
+        mounts.retain(|mount| mount.mount_point != mount_point);
+        mounts.push(Mount { mount_point, fs_type, source, space: None });
+    }
+    mounts.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    Ok(mounts)
+}
+
+/// Sizes of mounted filesystems read in the background, one thread per mount so that
+/// a hung network mount only holds up its own row.
+pub struct MountSpaces {
+    receiver: Receiver<(PathBuf, Option<SpaceInfo>)>,
+}
+
+impl MountSpaces {
+    pub fn start(mount_points: Vec<PathBuf>) -> Self {
+        let (sender, receiver) = channel();
+        for mount_point in mount_points {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let space = space_info(&mount_point);
+                let _ = sender.send((mount_point, space));
+            });
+        }
+
+        Self { receiver }
+    }
+
+    /// Mount points answered since the last call, with their size if statvfs succeeded.
+    pub fn poll(&self) -> Vec<(PathBuf, Option<SpaceInfo>)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// Type of the filesystem holding `path` (the longest mount point above it), without touching the filesystem itself.
+pub fn fs_type_of(path: &Path) -> Option<String> {
+    let path = fs::canonicalize(path).ok()?;
+    // Later entries win ties, like stacked mounts on the same point
+    mount_table().ok()?.into_iter().filter(|(mount_point, _, _)| path.starts_with(mount_point)).max_by_key(|(mount_point, _, _)| mount_point.as_os_str().len()).map(|(_, fs_type, _)| fs_type)
+}
+
+/// (mount point, filesystem type, source) for every line of the mount table, in mount order.
+fn mount_table() -> Result<Vec<(PathBuf, String, String)>, String> {
+    let content = fs::read_to_string("/proc/self/mountinfo").map_err(|e| format!("Cannot read mount table: {}", e))?;
+    let mut table = Vec::new();
+    for line in content.lines() {
+        // id parent major:minor root mount_point options [optional...] - fs_type source super_options
+        let fields: Vec<&str> = line.split(' ').collect();
+        let Some(separator) = fields.iter().position(|field| *field == "-") else { continue };
+        let (Some(mount_point), Some(fs_type), Some(source)) = (fields.get(4), fields.get(separator + 1), fields.get(separator + 2)) else { continue };
+        table.push((PathBuf::from(unescape(mount_point)), fs_type.to_string(), unescape(source)));
+    }
+    Ok(table)
+}
+
+/// mountinfo writes space, tab, newline and backslash as `\ooo` octal escapes.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let octal = bytes.get(index + 1..index + 4).filter(|_| bytes[index] == b'\\').and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match octal {
+            Some(byte) => {
+                out.push(byte);
+                index += 4;
+            }
+            None => {
+                out.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
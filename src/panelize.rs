@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, channel};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often a running command is checked for having finished or being cancelled.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A panelized set of paths shown instead of a directory listing.
+pub struct PanelList {
+    /// Where the list came from, shown in the path bar.
+    pub title: String,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Paths being collected in a background thread, so a slow command leaves the UI responsive.
+pub struct PanelizeRun {
+    receiver: Receiver<Result<PanelList, String>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PanelizeRun {
+    pub fn start(source: String, dir: PathBuf) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let result = collect_paths(&source, &dir, &thread_cancel);
+            if !thread_cancel.load(Ordering::Relaxed) {
+                let _ = sender.send(result);
+            }
+        });
+
+        Self { receiver, cancel }
+    }
+
+    /// The list, or why there is none, once collecting has finished.
+    pub fn poll(&self) -> Option<Result<PanelList, String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Stops collecting; a running command is killed.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for PanelizeRun {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Paths from `source`, one per line: the output of a shell command run in `dir`,
+/// or the contents of a file when written as `<file`. Relative paths resolve against `dir`.
+fn collect_paths(source: &str, dir: &Path, cancel: &AtomicBool) -> Result<PanelList, String> {
+    let source = source.trim();
+    let output = if let Some(file) = source.strip_prefix('<') {
+        let file = dir.join(file.trim());
+        fs::read_to_string(&file).map_err(|e| format!("Cannot read {}: {}", file.display(), e))?
+    } else {
+        run_command(source, dir, cancel)?
+    };
+
+    let mut seen = HashSet::new();
+    let paths: Vec<PathBuf> = output.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| dir.join(line))
+        .filter(|path| seen.insert(path.clone()))
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("No paths from \"{}\"", source));
+    }
+    Ok(PanelList { title: source.to_string(), paths })
+}
+
+/// Stdout of `command`. A failing command still counts if it printed something
+/// (grep and find exit non-zero on partial failures). Cancelling kills the command.
+fn run_command(command: &str, dir: &Path, cancel: &AtomicBool) -> Result<String, String> {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    #[cfg(unix)]
+    {
+        // Its own process group, so cancelling also stops whatever the shell started
+        use std::os::unix::process::CommandExt;
+        shell.process_group(0);
+    }
+    let mut child = shell.current_dir(dir).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().map_err(|e| format!("Cannot run command: {}", e))?;
+    // Both pipes are drained while waiting, so a chatty command cannot block on a full pipe
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            kill(&mut child);
+            return Err("Cancelled".to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Cannot run command: {}", e)),
+        }
+    };
+    let stdout = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+    if !status.success() && stdout.trim().is_empty() {
+        let stderr = stderr.join().unwrap_or_default();
+        return Err(format!("Command failed ({}): {}", status, String::from_utf8_lossy(&stderr).trim()));
+    }
+    Ok(stdout)
+}
+
+/// Reads a child's pipe to the end in its own thread.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    // The negative id addresses the whole process group
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+    let _ = child.wait();
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
@@ -0,0 +1,94 @@
+use crate::app::Item;
+use crate::fs_ops::{PanelFilter, load_directory_rows, open_regular_file};
+use crate::viewer::is_binary_file;
+use std::io::{Error, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Only the head of a file is read for previews.
+const PREVIEW_BYTES: u64 = 64 * 1024;
+/// The cursor has to rest this long before a preview is loaded.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub enum PreviewContent {
+    Dir(Vec<Item>),
+    Text(Vec<String>),
+    Binary,
+    Error(String),
+}
+
+/// A cheap look at a path for side panes, loaded off the UI thread; cached by `path` so it is built once per cursor move.
+pub struct Preview {
+    pub path: PathBuf,
+    /// None until the load finishes.
+    pub content: Option<PreviewContent>,
+    filter: PanelFilter,
+    requested_at: Instant,
+    receiver: Option<Receiver<PreviewContent>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Preview {
+    /// A preview of `path`; loading starts once `poll` sees the cursor has settled.
+    pub fn request(path: PathBuf, filter: &PanelFilter) -> Self {
+        Self { path, content: None, filter: filter.clone(), requested_at: Instant::now(), receiver: None, cancel: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Starts a due load and takes its result once done.
+    pub fn poll(&mut self) {
+        if self.content.is_none() && self.receiver.is_none() && self.requested_at.elapsed() >= DEBOUNCE {
+            let (sender, receiver) = channel();
+            let (path, filter, cancel) = (self.path.clone(), self.filter.clone(), Arc::clone(&self.cancel));
+            thread::spawn(move || {
+                let content = load(&path, &filter);
+                if !cancel.load(Ordering::Relaxed) {
+                    let _ = sender.send(content);
+                }
+            });
+            self.receiver = Some(receiver);
+        }
+        if let Some(receiver) = &self.receiver && let Ok(content) = receiver.try_recv() {
+            self.content = Some(content);
+            self.receiver = None;
+        }
+    }
+}
+
+impl Drop for Preview {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn load(path: &Path, filter: &PanelFilter) -> PreviewContent {
+    if path.is_dir() {
+        match load_directory_rows(path, filter) {
+            Ok((items, _, _)) => PreviewContent::Dir(items.into_iter().filter(|item| item.name != "..").collect()),
+            Err(e) => PreviewContent::Error(e.to_string()),
+        }
+    } else {
+        match is_binary_file(path) {
+            Ok(true) => PreviewContent::Binary,
+            Ok(false) => read_head(path),
+            Err(e) => PreviewContent::Error(e.to_string()),
+        }
+    }
+}
+
+fn read_head(path: &Path) -> PreviewContent {
+    match read_head_bytes(path, PREVIEW_BYTES) {
+        Ok(buffer) => PreviewContent::Text(String::from_utf8_lossy(&buffer).lines().map(str::to_string).collect()),
+        Err(e) => PreviewContent::Error(e.to_string()),
+    }
+}
+
+/// Up to `limit` bytes from the start of the file.
+pub fn read_head_bytes(path: &Path, limit: u64) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    open_regular_file(path)?.take(limit).read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
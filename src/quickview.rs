@@ -0,0 +1,158 @@
+use crate::constants::TAB_SPACES;
+use crate::preview::read_head_bytes;
+use crate::utils::{display_name, hex_dump};
+use crate::viewer::{highlight_content, is_binary_file};
+use ratatui::text::Span;
+use std::fs::{read_dir, symlink_metadata};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The cursor has to rest this long before a preview is built.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+const TEXT_BYTES: u64 = 64 * 1024;
+const TEXT_LINES: usize = 500;
+const HEX_BYTES: u64 = 4096;
+/// Directory walks report a partial summary every this many entries.
+const SUMMARY_INTERVAL: usize = 2000;
+
+#[derive(Clone, Default)]
+pub struct DirSummary {
+    pub dirs: usize,
+    pub files: usize,
+    pub total_size: u64,
+    /// Most recently modified file, relative to the summarized directory.
+    pub newest: Option<(String, SystemTime)>,
+    /// Subdirectories that could not be read.
+    pub unreadable: usize,
+    pub complete: bool,
+}
+
+pub enum QuickViewContent {
+    Text(Vec<Vec<Span<'static>>>),
+    Dir(DirSummary),
+    Hex(Vec<String>),
+    Error(String),
+}
+
+/// Ctrl+Q state: a preview of the active panel's cursor row, built off the UI thread.
+pub struct QuickView {
+    /// Path being shown, or waiting for the debounce to expire.
+    pub path: Option<PathBuf>,
+    pub content: Option<QuickViewContent>,
+    requested_at: Instant,
+    receiver: Option<Receiver<QuickViewContent>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl QuickView {
+    pub fn new() -> Self {
+        Self { path: None, content: None, requested_at: Instant::now(), receiver: None, cancel: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Points the view at `path`; loading starts once `poll` sees the cursor has settled.
+    pub fn request(&mut self, path: Option<PathBuf>) {
+        if path == self.path {
+            return;
+        }
+        self.cancel();
+        self.path = path;
+        self.content = None;
+        self.receiver = None;
+        self.requested_at = Instant::now();
+    }
+
+    /// Starts a due load and drains finished (or partial) results.
+    pub fn poll(&mut self) {
+        if let Some(path) = &self.path && self.content.is_none() && self.receiver.is_none() && self.requested_at.elapsed() >= DEBOUNCE {
+            let (sender, receiver) = channel();
+            self.cancel = Arc::new(AtomicBool::new(false));
+            let thread_path = path.clone();
+            let thread_cancel = Arc::clone(&self.cancel);
+            thread::spawn(move || load(&thread_path, &sender, &thread_cancel));
+            self.receiver = Some(receiver);
+        }
+        if let Some(receiver) = &self.receiver {
+            while let Ok(content) = receiver.try_recv() {
+                self.content = Some(content);
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for QuickView {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn load(path: &Path, sender: &Sender<QuickViewContent>, cancel: &AtomicBool) {
+    if path.is_dir() {
+        summarize_dir(path, sender, cancel);
+        return;
+    }
+    let content = match is_binary_file(path) {
+        Ok(true) => match read_head_bytes(path, HEX_BYTES) {
+            Ok(bytes) => QuickViewContent::Hex(hex_dump(&bytes, 0)),
+            Err(e) => QuickViewContent::Error(e.to_string()),
+        },
+        Ok(false) => match read_head_bytes(path, TEXT_BYTES) {
+            Ok(bytes) => {
+                let lines: Vec<String> = String::from_utf8_lossy(&bytes).lines().take(TEXT_LINES).map(|line| line.replace('\t', TAB_SPACES)).collect();
+                let extension = path.extension().map(|ext| ext.to_string_lossy().into_owned()).unwrap_or_default();
+                QuickViewContent::Text(highlight_content(&lines, &extension))
+            }
+            Err(e) => QuickViewContent::Error(e.to_string()),
+        },
+        Err(e) => QuickViewContent::Error(e.to_string()),
+    };
+    let _ = sender.send(content);
+}
+
+/// Recursive counts, total size and newest file; symlinks are counted but not followed.
+fn summarize_dir(root: &Path, sender: &Sender<QuickViewContent>, cancel: &AtomicBool) {
+    let mut summary = DirSummary::default();
+    let mut stack = vec![root.to_path_buf()];
+    let mut seen = 0usize;
+
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = read_dir(&dir) else {
+            if dir != root {
+                summary.unreadable += 1;
+            }
+            continue;
+        };
+        for entry in entries.flatten() {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let path = entry.path();
+            let Ok(metadata) = symlink_metadata(&path) else { continue };
+            if metadata.is_dir() {
+                summary.dirs += 1;
+                stack.push(path);
+            } else {
+                summary.files += 1;
+                summary.total_size += metadata.len();
+                if let Ok(modified) = metadata.modified() && summary.newest.as_ref().is_none_or(|(_, newest)| modified > *newest) {
+                    let relative = display_name(path.strip_prefix(root).unwrap_or(&path).as_os_str());
+                    summary.newest = Some((relative, modified));
+                }
+            }
+            seen += 1;
+            if seen.is_multiple_of(SUMMARY_INTERVAL) && sender.send(QuickViewContent::Dir(summary.clone())).is_err() {
+                return;
+            }
+        }
+    }
+
+    summary.complete = true;
+    let _ = sender.send(QuickViewContent::Dir(summary));
+}
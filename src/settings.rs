@@ -0,0 +1,65 @@
+use crate::watch::WatchMode;
+use std::env;
+use std::fs;
+use std::io::Error;
+use std::path::PathBuf;
+
+pub const SPLIT_MIN: u16 = 10;
+pub const SPLIT_MAX: u16 = 90;
+
+/// Preferences remembered across runs, stored as `key=value` lines.
+pub struct Settings {
+    /// Share of the file area given to the left (or top) panel, in percent.
+    pub split_percent: u16,
+    pub is_split_horizontal: bool,
+    pub watch_mode: WatchMode,
+    /// Copies recreate device nodes instead of refusing them (usually needs root).
+    pub copy_devices: bool,
+}
+
+impl Settings {
+    /// Missing or unreadable settings fall back to defaults.
+    pub fn load() -> Self {
+        let mut settings = Self { split_percent: 50, is_split_horizontal: false, watch_mode: WatchMode::Auto, copy_devices: false };
+        let Some(content) = settings_path().and_then(|path| fs::read_to_string(path).ok()) else {
+            return settings;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            match key.trim() {
+                "split_percent" => {
+                    if let Ok(percent) = value.trim().parse::<u16>() {
+                        settings.split_percent = percent.clamp(SPLIT_MIN, SPLIT_MAX);
+                    }
+                }
+                "split_horizontal" => settings.is_split_horizontal = value.trim() == "true",
+                "watch" => {
+                    if let Some(mode) = WatchMode::parse(value.trim()) {
+                        settings.watch_mode = mode;
+                    }
+                }
+                "copy_devices" => settings.copy_devices = value.trim() == "true",
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = settings_path() else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, format!("split_percent={}\nsplit_horizontal={}\nwatch={}\ncopy_devices={}\n", self.split_percent, self.is_split_horizontal, self.watch_mode.label(), self.copy_devices))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/fm84/settings`, falling back to `~/.config` (or `%APPDATA%` on Windows).
+fn settings_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(config_dir.join("fm84").join("settings"))
+}
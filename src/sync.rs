@@ -0,0 +1,383 @@
+use crate::compare::{CompareMethod, files_match, is_close};
+use crate::fs_ops::{copy_path, delete_path};
+use crate::utils::display_name;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fs::{self, Metadata, read_dir, symlink_metadata};
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::SystemTime;
+
+const PROGRESS_INTERVAL: usize = 256;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SyncField {
+    Direction,
+    Method,
+    DeleteExtras,
+}
+
+impl SyncField {
+    pub const ALL: [SyncField; 3] = [SyncField::Direction, SyncField::Method, SyncField::DeleteExtras];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SyncField::Direction => "Direction",
+            SyncField::Method => "Compare by",
+            SyncField::DeleteExtras => "Delete extras",
+        }
+    }
+}
+
+/// Sync dialog (Alt+Y) options.
+pub struct SyncDialog {
+    /// Newer files win in both directions instead of the right mirroring the left.
+    pub is_two_way: bool,
+    pub method: CompareMethod,
+    /// Mirror mode only: remove entries that exist on the right alone.
+    pub delete_extras: bool,
+    pub focus: usize,
+}
+
+impl SyncDialog {
+    pub fn new() -> Self {
+        Self { is_two_way: false, method: CompareMethod::Quick, delete_extras: false, focus: 0 }
+    }
+
+    pub fn focus_by(&mut self, delta: isize) {
+        let count = SyncField::ALL.len() as isize;
+        self.focus = (self.focus as isize + delta).rem_euclid(count) as usize;
+    }
+
+    pub fn toggle_focused(&mut self) {
+        match SyncField::ALL[self.focus] {
+            SyncField::Direction => self.is_two_way = !self.is_two_way,
+            SyncField::Method => self.method = if self.method == CompareMethod::Quick { CompareMethod::Content } else { CompareMethod::Quick },
+            SyncField::DeleteExtras => self.delete_extras = !self.delete_extras,
+        }
+    }
+
+    pub fn value(&self, field: SyncField) -> &'static str {
+        match field {
+            SyncField::Direction if self.is_two_way => "Both ways (newer wins)",
+            SyncField::Direction => "Left -> right",
+            SyncField::Method => self.method.label(),
+            SyncField::DeleteExtras if self.is_two_way => "n/a",
+            SyncField::DeleteExtras if self.delete_extras => "[x]",
+            SyncField::DeleteExtras => "[ ]",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SyncKind {
+    /// Missing on the target side.
+    Copy,
+    /// Differs on the target side and gets overwritten.
+    Update,
+    /// Only on the target side.
+    Delete,
+}
+
+impl SyncKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SyncKind::Copy => "copy",
+            SyncKind::Update => "update",
+            SyncKind::Delete => "delete",
+        }
+    }
+}
+
+/// One step of a sync plan, relative to the two roots.
+#[derive(Clone)]
+pub struct SyncAction {
+    pub kind: SyncKind,
+    pub relative: PathBuf,
+    pub is_dir: bool,
+    /// The right side is the one changed (for deletes, the side the entry is removed from).
+    pub to_right: bool,
+    pub enabled: bool,
+}
+
+impl SyncAction {
+    /// `update -> docs/notes.txt`; the arrow points at the side that changes.
+    pub fn describe(&self) -> String {
+        let arrow = if self.to_right { "->" } else { "<-" };
+        let slash = if self.is_dir { "/" } else { "" };
+        format!("{:<6} {} {}{}", self.kind.label(), arrow, display_name(self.relative.as_os_str()), slash)
+    }
+
+    fn run(&self, left_root: &Path, right_root: &Path, allow_devices: bool) -> Result<(), Error> {
+        let (source_root, target_root) = if self.to_right { (left_root, right_root) } else { (right_root, left_root) };
+        let target = target_root.join(&self.relative);
+        match self.kind {
+            SyncKind::Copy => copy_path(source_root.join(&self.relative), target, self.is_dir, allow_devices),
+            SyncKind::Update => {
+                // A file replacing a directory (or the other way round) needs the old entry gone first
+                if let Ok(metadata) = symlink_metadata(&target) && metadata.is_dir() != self.is_dir {
+                    delete_path(target.clone(), metadata.is_dir())?;
+                }
+                copy_path(source_root.join(&self.relative), target, self.is_dir, allow_devices)
+            }
+            SyncKind::Delete => delete_path(target, self.is_dir),
+        }
+    }
+}
+
+enum SyncMessage {
+    Action(SyncAction),
+    Progress(usize),
+    Done,
+}
+
+/// A sync plan being built in the background, then reviewed and executed.
+pub struct SyncPlan {
+    pub left_root: PathBuf,
+    pub right_root: PathBuf,
+    pub actions: Vec<SyncAction>,
+    pub selected: usize,
+    pub scanned: usize,
+    pub done: bool,
+    /// Shown in the plan's status line, e.g. where it was exported.
+    pub note: String,
+    /// Set once the plan is being executed.
+    pub run: Option<SyncRun>,
+    receiver: Receiver<SyncMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl SyncPlan {
+    pub fn start(left_root: PathBuf, right_root: PathBuf, dialog: &SyncDialog) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut planner = Planner {
+            left_root: left_root.clone(),
+            right_root: right_root.clone(),
+            is_two_way: dialog.is_two_way,
+            method: dialog.method,
+            delete_extras: dialog.delete_extras && !dialog.is_two_way,
+            sender,
+            cancel: Arc::clone(&cancel),
+            scanned: 0,
+        };
+        thread::spawn(move || {
+            planner.walk(Path::new(""));
+            let _ = planner.sender.send(SyncMessage::Done);
+        });
+
+        Self { left_root, right_root, actions: Vec::new(), selected: 0, scanned: 0, done: false, note: String::new(), run: None, receiver, cancel }
+    }
+
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                SyncMessage::Action(action) => self.actions.push(action),
+                SyncMessage::Progress(scanned) => self.scanned = scanned,
+                SyncMessage::Done => self.done = true,
+            }
+        }
+        if let Some(run) = &mut self.run {
+            run.poll();
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn select_by(&mut self, delta: isize) {
+        let max = self.actions.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, max) as usize;
+    }
+
+    /// Toggles the action under the cursor and moves to the next one, like Space in the panels.
+    pub fn toggle_selected(&mut self) {
+        if let Some(action) = self.actions.get_mut(self.selected) {
+            action.enabled = !action.enabled;
+            self.select_by(1);
+        }
+    }
+
+    pub fn invert_all(&mut self) {
+        for action in &mut self.actions {
+            action.enabled = !action.enabled;
+        }
+    }
+
+    pub fn enabled_count(&self) -> usize {
+        self.actions.iter().filter(|action| action.enabled).count()
+    }
+
+    /// Starts running the enabled actions in the background; see `SyncRun`.
+    pub fn execute(&mut self, allow_devices: bool) {
+        let actions = self.actions.iter().filter(|action| action.enabled).cloned().collect();
+        self.run = Some(SyncRun::start(actions, self.left_root.clone(), self.right_root.clone(), allow_devices));
+    }
+
+    /// Writes the plan as text, one action per line, disabled ones marked `[ ]`.
+    pub fn export(&self, path: &Path) -> Result<(), Error> {
+        let mut text = format!("# Sync plan\n# left:  {}\n# right: {}\n", self.left_root.display(), self.right_root.display());
+        for action in &self.actions {
+            let check = if action.enabled { "[x]" } else { "[ ]" };
+            text.push_str(&format!("{} {}\n", check, action.describe()));
+        }
+        fs::write(path, text)
+    }
+}
+
+impl Drop for SyncPlan {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+enum RunMessage {
+    /// Index and description of the action being started.
+    Started(usize, String),
+    Failed(String),
+    /// Number of actions completed.
+    Done(usize),
+}
+
+/// Actions of a plan executed in order off the UI thread, stopping at the first failure.
+/// Cancelling stops before the next action; the one in progress is finished.
+pub struct SyncRun {
+    pub total: usize,
+    pub completed: usize,
+    /// Description of the action in progress.
+    pub current: String,
+    pub error: Option<String>,
+    pub done: bool,
+    receiver: Receiver<RunMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl SyncRun {
+    fn start(actions: Vec<SyncAction>, left_root: PathBuf, right_root: PathBuf, allow_devices: bool) -> Self {
+        let (sender, receiver) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let total = actions.len();
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let mut completed = 0;
+            for (index, action) in actions.iter().enumerate() {
+                if thread_cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let _ = sender.send(RunMessage::Started(index, action.describe()));
+                if let Err(e) = action.run(&left_root, &right_root, allow_devices) {
+                    let _ = sender.send(RunMessage::Failed(format!("{}: {}", action.describe(), e)));
+                    break;
+                }
+                completed += 1;
+            }
+            let _ = sender.send(RunMessage::Done(completed));
+        });
+
+        Self { total, completed: 0, current: String::new(), error: None, done: false, receiver, cancel }
+    }
+
+    fn poll(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                RunMessage::Started(index, description) => {
+                    self.completed = index;
+                    self.current = description;
+                }
+                RunMessage::Failed(e) => self.error = Some(e),
+                RunMessage::Done(completed) => {
+                    self.completed = completed;
+                    self.done = true;
+                }
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for SyncRun {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+struct Planner {
+    left_root: PathBuf,
+    right_root: PathBuf,
+    is_two_way: bool,
+    method: CompareMethod,
+    delete_extras: bool,
+    sender: Sender<SyncMessage>,
+    cancel: Arc<AtomicBool>,
+    scanned: usize,
+}
+
+impl Planner {
+    /// Plans `relative` (a directory present on both sides) and the subdirectories they share, in name order.
+    fn walk(&mut self, relative: &Path) {
+        // An unreadable side is skipped rather than treated as empty, which would plan deleting everything
+        let (Some(left_entries), Some(right_entries)) = (entries(&self.left_root.join(relative)), entries(&self.right_root.join(relative))) else { return };
+        let names: BTreeSet<&OsString> = left_entries.keys().chain(right_entries.keys()).collect();
+
+        for name in names {
+            if self.cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            self.scanned += 1;
+            if self.scanned.is_multiple_of(PROGRESS_INTERVAL) {
+                let _ = self.sender.send(SyncMessage::Progress(self.scanned));
+            }
+            let relative = relative.join(name);
+            match (left_entries.get(name), right_entries.get(name)) {
+                (Some(left), None) => self.push(SyncKind::Copy, relative, left.is_dir(), true, true),
+                (None, Some(right)) if self.is_two_way => self.push(SyncKind::Copy, relative, right.is_dir(), false, true),
+                (None, Some(right)) if self.delete_extras => self.push(SyncKind::Delete, relative, right.is_dir(), true, true),
+                (Some(left), Some(right)) if left.is_dir() && right.is_dir() => self.walk(&relative),
+                // A file on one side and a directory on the other is a conflict when both sides count
+                (Some(left), Some(right)) if left.is_dir() != right.is_dir() => self.push(SyncKind::Update, relative, left.is_dir(), true, !self.is_two_way),
+                (Some(left), Some(right)) => self.plan_file(relative, left, right),
+                _ => {}
+            }
+        }
+    }
+
+    fn plan_file(&self, relative: PathBuf, left: &Metadata, right: &Metadata) {
+        if files_match(&self.left_root.join(&relative), left, &self.right_root.join(&relative), right, self.method) {
+            return;
+        }
+        if !self.is_two_way {
+            self.push(SyncKind::Update, relative, false, true, true);
+            return;
+        }
+        let left_time = left.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let right_time = right.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if is_close(left_time, right_time) {
+            // Changed on both sides at once: left wins only if the user opts in
+            self.push(SyncKind::Update, relative, false, true, false);
+        } else {
+            self.push(SyncKind::Update, relative, false, left_time > right_time, true);
+        }
+    }
+
+    fn push(&self, kind: SyncKind, relative: PathBuf, is_dir: bool, to_right: bool, enabled: bool) {
+        let _ = self.sender.send(SyncMessage::Action(SyncAction { kind, relative, is_dir, to_right, enabled }));
+    }
+}
+
+/// Entries of `dir` by name, not following symlinks.
+fn entries(dir: &Path) -> Option<BTreeMap<OsString, Metadata>> {
+    let entries = read_dir(dir).ok()?;
+    Some(entries.flatten().filter_map(|entry| Some((entry.file_name(), symlink_metadata(entry.path()).ok()?))).collect())
+}
@@ -1,16 +1,27 @@
-use crate::app::AppState;
+use crate::app::{AppState, Item, PanelLayout};
+use crate::columns::{Column, PanelView, ViewMode};
+use crate::compare::CompareFlag;
 use crate::constants::*;
+use crate::find::FindField;
+use crate::fs_ops::{PanelFilter, format_time, format_time_opt};
+use crate::loader::DirLoad;
+use crate::mounts::SpaceInfo;
+use crate::preview::PreviewContent;
+use crate::quickview::QuickViewContent;
+use crate::sync::SyncField;
+use crate::viewer::{HEX_ROW_BYTES, HexEdit, ViewerMode};
 use crate::utils::*;
 use chrono::Local;
 use ratatui::{
     Terminal,
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
 };
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 // Pre-computed styles used throughout rendering
 const STYLE_BORDER: Style = Style::new().fg(COLOR_BORDER);
@@ -20,6 +31,9 @@ const STYLE_FILE: Style = Style::new().fg(COLOR_FILE);
 const STYLE_DIR: Style = Style::new().fg(COLOR_DIRECTORY);
 const STYLE_DIR_DARK: Style = Style::new().fg(COLOR_DIRECTORY_DARK);
 
+const NORMAL_WIDTHS: [Constraint; 8] = [Constraint::Length(2), Constraint::Percentage(50), Constraint::Length(1), Constraint::Percentage(10), Constraint::Length(1), Constraint::Percentage(15), Constraint::Length(1), Constraint::Length(15)];
+const BRIEF_COLUMN_WIDTH: u16 = 24;
+
 pub fn render_ui<B: Backend>(terminal: &mut Terminal<B>, app_state: &mut AppState) {
     // Update cached clock
     let current_time = Local::now().format(" %H:%M:%S ").to_string();
@@ -27,6 +41,14 @@ pub fn render_ui<B: Backend>(terminal: &mut Terminal<B>, app_state: &mut AppStat
         app_state.cached_clock = current_time;
     }
 
+    if app_state.is_miller && !app_state.is_f3_displayed && !app_state.is_f4_displayed {
+        app_state.refresh_miller();
+    }
+    if app_state.is_quick_view && !app_state.is_f3_displayed && !app_state.is_f4_displayed {
+        app_state.refresh_quick_view();
+    }
+    app_state.refresh_space();
+
     let _ = terminal.draw(|f| {
         let area = f.area();
 
@@ -48,11 +70,15 @@ pub fn render_ui<B: Backend>(terminal: &mut Terminal<B>, app_state: &mut AppStat
             .split(area);
 
         render_top_panel(f, chunks_main[0], &app_state.cached_clock);
-        render_path_bar(f, chunks_main[1], &app_state.dir_left, &app_state.dir_right, area.width, app_state.is_left_active);
+        render_path_bar(f, chunks_main[1], app_state, area.width);
         if app_state.is_f3_displayed {
             app_state.viewer_viewport_height = render_viewer(f, chunks_main[2], app_state);
         } else if app_state.is_f4_displayed {
             app_state.editor_viewport_height = render_editor(f, chunks_main[2], app_state);
+        } else if app_state.is_du_displayed {
+            app_state.page_size = render_disk_usage(f, chunks_main[2], app_state);
+        } else if app_state.is_miller {
+            app_state.page_size = render_miller_columns(f, chunks_main[2], app_state);
         } else {
             app_state.page_size = render_file_tables(f, chunks_main[2], app_state);
         }
@@ -73,6 +99,22 @@ pub fn render_ui<B: Backend>(terminal: &mut Terminal<B>, app_state: &mut AppStat
             render_create_popup(f, area, app_state);
         } else if app_state.is_f8_displayed {
             render_delete_popup(f, area, app_state);
+        } else if app_state.is_panelize_displayed {
+            render_panelize_popup(f, area, app_state);
+        } else if app_state.is_sync_displayed {
+            render_sync_popup(f, area, app_state);
+        } else if app_state.is_compare_displayed {
+            render_compare_popup(f, area, app_state);
+        } else if app_state.is_mounts_displayed {
+            render_mounts_popup(f, area, app_state);
+        } else if app_state.is_filter_displayed {
+            render_filter_popup(f, area, app_state);
+        } else if app_state.is_find_displayed {
+            render_find_popup(f, area, app_state);
+        } else if app_state.is_fuzzy_displayed {
+            render_fuzzy_popup(f, area, app_state);
+        } else if app_state.is_columns_displayed {
+            render_columns_popup(f, area, app_state);
         }
     });
 }
@@ -92,19 +134,29 @@ fn render_top_panel(f: &mut ratatui::Frame<'_>, area: Rect, cached_clock: &str)
     f.render_widget(block_top, area);
 }
 
-fn render_path_bar(f: &mut ratatui::Frame<'_>, area: Rect, dir_left: &PathBuf, dir_right: &PathBuf, total_width: u16, is_left_active: bool) {
-    let length_left = ((total_width as usize).saturating_sub(3)) / 2;
-    let length_right = ((total_width as usize).saturating_sub(2)) / 2;
-
-    let path_left = limit_path_string(dir_left, length_left.saturating_sub(8));
-    let path_right = limit_path_string(dir_right, length_right.saturating_sub(8));
-
-    let (color_left, color_right) = if is_left_active {
+fn render_path_bar(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState, total_width: u16) {
+    let (color_left, color_right) = if app_state.is_left_active {
         (STYLE_DIR, STYLE_DIR_DARK)
     } else {
         (STYLE_DIR_DARK, STYLE_DIR)
     };
 
+    // Zoomed: only the active panel; top/bottom split: the top panel, the bottom one labels the separator
+    if !app_state.is_miller && (app_state.is_zoomed || app_state.is_split_horizontal) {
+        let is_left = !app_state.is_zoomed || app_state.is_left_active;
+        let label = panel_path_label(app_state, is_left, (total_width as usize).saturating_sub(8));
+        let style = if is_left { color_left } else { color_right };
+        f.render_widget(Paragraph::new(single_path_line(label, style, total_width)), area);
+        return;
+    }
+
+    let separator = if app_state.is_miller { split_offset(total_width, 50) } else { split_offset(total_width, app_state.split_percent) } as usize;
+    let length_left = separator.saturating_sub(1);
+    let length_right = (total_width as usize).saturating_sub(separator + 2);
+
+    let path_left = panel_path_label(app_state, true, length_left.saturating_sub(8));
+    let path_right = panel_path_label(app_state, false, length_right.saturating_sub(8));
+
     let border_line = vec![
         Span::styled("├──", STYLE_BORDER),
         Span::styled(format!(" {} ", path_left), color_left),
@@ -116,10 +168,64 @@ fn render_path_bar(f: &mut ratatui::Frame<'_>, area: Rect, dir_left: &PathBuf, d
     f.render_widget(Paragraph::new(Line::from(border_line)), area);
 }
 
-fn render_file_tables(f: &mut ratatui::Frame<'_>, chunk: Rect, app_state: &mut AppState) -> u16 {
-    let chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(50), Constraint::Length(1), Constraint::Percentage(50)]).split(chunk);
+/// A full-width `├── label ───┤` line.
+fn single_path_line(label: String, style: Style, width: u16) -> Line<'static> {
+    let dashes = (width as usize).saturating_sub(label.chars().count() + 6);
+    Line::from(vec![
+        Span::styled("├──", STYLE_BORDER),
+        Span::styled(format!(" {} ", label), style),
+        Span::styled(format!("{}┤", "─".repeat(dashes)), STYLE_BORDER),
+    ])
+}
+
+/// Path bar text for one panel: its directory, or the previewed path when it shows the quick view.
+fn panel_path_label(app_state: &AppState, is_left: bool, n: usize) -> String {
+    if app_state.is_quick_view && app_state.is_left_active != is_left {
+        return format!("Quick view: {}", app_state.quick_view.path.as_deref().map(|path| limit_path_string(path, n.saturating_sub(12))).unwrap_or_default());
+    }
+    if let Some(list) = if is_left { &app_state.list_left } else { &app_state.list_right } {
+        let title: String = list.title.chars().take(n.saturating_sub(6)).collect();
+        return format!("List: {}", title);
+    }
+    let (listing_error, total, unreadable) = if is_left {
+        (&app_state.listing_error_left, app_state.total_left, app_state.unreadable_left)
+    } else {
+        (&app_state.listing_error_right, app_state.total_right, app_state.unreadable_right)
+    };
+    let mut warnings = Vec::new();
+    if let Some(error) = listing_error {
+        warnings.push(format!("{}: {}", if total == 0 { "unreadable" } else { "incomplete" }, error));
+    }
+    if unreadable > 0 {
+        warnings.push(format!("{} unreadable", unreadable));
+    }
+    if is_left {
+        path_with_filter(&app_state.dir_left, &app_state.filter_left, app_state.view_left.mode, warnings, n)
+    } else {
+        path_with_filter(&app_state.dir_right, &app_state.filter_right, app_state.view_right.mode, warnings, n)
+    }
+}
+
+/// Path limited to `n` chars, with the active filter, a non-default view mode and read problems appended as " [mask, brief, 2 unreadable]".
+fn path_with_filter(dir: &Path, filter: &PanelFilter, mode: ViewMode, warnings: Vec<String>, n: usize) -> String {
+    let mut parts = Vec::new();
+    if filter.is_active() {
+        parts.push(filter.label());
+    }
+    if mode != ViewMode::Normal {
+        parts.push(mode.label().to_string());
+    }
+    parts.extend(warnings);
+    if parts.is_empty() {
+        return limit_path_string(dir, n);
+    }
+    let label = format!(" [{}]", parts.join(", "));
+    format!("{}{}", limit_path_string(dir, n.saturating_sub(label.len())), label)
+}
 
-    let widths = [Constraint::Length(2), Constraint::Percentage(50), Constraint::Length(1), Constraint::Percentage(10), Constraint::Length(1), Constraint::Percentage(15), Constraint::Length(1), Constraint::Length(15)];
+fn render_file_tables(f: &mut ratatui::Frame<'_>, chunk: Rect, app_state: &mut AppState) -> u16 {
+    let layout = panel_layout(chunk, app_state);
+    app_state.panel_layout = layout;
 
     let is_f2_displayed = app_state.is_f2_displayed;
     let table_style = |active: bool| {
@@ -133,45 +239,332 @@ fn render_file_tables(f: &mut ratatui::Frame<'_>, chunk: Rect, app_state: &mut A
             .add_modifier(Modifier::BOLD)
     };
 
-    // Viewport height (subtract 1 for header row)
-    let viewport_height = chunks[0].height.saturating_sub(1) as usize;
+    // Side by side panels share the separator; otherwise each panel draws both side borders
+    let is_side_by_side = !app_state.is_zoomed && !app_state.is_split_horizontal;
+    for is_left in [true, false] {
+        let area = if is_left { layout.left } else { layout.right };
+        if area.is_empty() {
+            continue;
+        }
+        let borders = if !is_side_by_side { Borders::LEFT | Borders::RIGHT } else if is_left { Borders::LEFT } else { Borders::RIGHT };
+        let block = Block::default().borders(borders).border_style(STYLE_BORDER);
+        let is_active = app_state.is_left_active == is_left;
+        if app_state.is_quick_view && !is_active {
+            render_quick_view(f, area, app_state, block);
+        } else {
+            render_panel(f, area, app_state, is_left, block, table_style(is_active));
+        }
+    }
 
-    let header = make_header_row();
+    if app_state.is_split_horizontal && !layout.separator.is_empty() {
+        // The bottom panel's path line doubles as the separator
+        let label = panel_path_label(app_state, false, (layout.separator.width as usize).saturating_sub(8));
+        let style = if app_state.is_left_active { STYLE_DIR_DARK } else { STYLE_DIR };
+        f.render_widget(Paragraph::new(single_path_line(label, style, layout.separator.width)), layout.separator);
+    } else if !layout.separator.is_empty() {
+        // Cache the separator string based on height
+        let separator_height = layout.separator.height;
+        if app_state.cached_separator_height != separator_height {
+            app_state.cached_separator_height = separator_height;
+            app_state.cached_separator = "│\n".repeat(separator_height.saturating_sub(1) as usize) + "│";
+        }
+        let separator_vertical = Paragraph::new(Text::raw(&app_state.cached_separator)).style(STYLE_BORDER);
+        f.render_widget(separator_vertical, layout.separator);
+    }
 
-    // Build only visible rows for left panel
-    let (rows_left, offset_left) = build_viewport_rows(app_state, true, viewport_height);
-    let mut state_left_view = TableState::default();
-    state_left_view.select(app_state.state_left.selected().map(|s| s.saturating_sub(offset_left)));
+    if app_state.is_left_active { layout.left.height } else { layout.right.height }
+}
 
-    let table_left = Table::new(rows_left, widths.clone())
-        .block(Block::default().borders(Borders::LEFT).border_style(STYLE_BORDER))
-        .header(header.clone())
-        .row_highlight_style(table_style(app_state.is_left_active))
-        .column_spacing(1);
-    f.render_stateful_widget(table_left, chunks[0], &mut state_left_view);
+/// Splits the file area between the panels by ratio and orientation; zoom gives the active panel everything.
+fn panel_layout(area: Rect, app_state: &AppState) -> PanelLayout {
+    let empty = Rect::default();
+    if app_state.is_zoomed {
+        let (left, right) = if app_state.is_left_active { (area, empty) } else { (empty, area) };
+        return PanelLayout { area, left, separator: empty, right };
+    }
+    if app_state.is_split_horizontal {
+        let top = split_offset(area.height, app_state.split_percent);
+        PanelLayout {
+            area,
+            left: Rect::new(area.x, area.y, area.width, top),
+            separator: Rect::new(area.x, area.y + top, area.width, 1),
+            right: Rect::new(area.x, area.y + top + 1, area.width, area.height.saturating_sub(top + 1)),
+        }
+    } else {
+        let left = split_offset(area.width, app_state.split_percent);
+        PanelLayout {
+            area,
+            left: Rect::new(area.x, area.y, left, area.height),
+            separator: Rect::new(area.x + left, area.y, 1, area.height),
+            right: Rect::new(area.x + left + 1, area.y, area.width.saturating_sub(left + 1), area.height),
+        }
+    }
+}
 
-    // Cache the separator string based on height
-    let separator_height = chunks[0].height;
-    if app_state.cached_separator_height != separator_height {
-        app_state.cached_separator_height = separator_height;
-        app_state.cached_separator = "│\n".repeat(separator_height.saturating_sub(1) as usize) + "│";
+/// Cells before the separator when `length` cells are split at `percent`.
+fn split_offset(length: u16, percent: u16) -> u16 {
+    (length.saturating_sub(1) as u32 * percent as u32 / 100) as u16
+}
+
+/// Draws one file panel in its view mode: a table (normal/full) or brief name columns.
+fn render_panel(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState, is_left: bool, block: Block<'static>, highlight: Style) {
+    let view = if is_left { &app_state.view_left } else { &app_state.view_right };
+    if view.mode == ViewMode::Brief {
+        render_brief_panel(f, area, app_state, is_left, block, highlight);
+        return;
     }
-    let separator_vertical = Paragraph::new(Text::raw(&app_state.cached_separator)).style(STYLE_BORDER);
-    f.render_widget(separator_vertical, chunks[1]);
 
-    // Build only visible rows for right panel
-    let (rows_right, offset_right) = build_viewport_rows(app_state, false, viewport_height);
-    let mut state_right_view = TableState::default();
-    state_right_view.select(app_state.state_right.selected().map(|s| s.saturating_sub(offset_right)));
+    let (header, widths) = if view.mode == ViewMode::Full { full_view_layout(view) } else { (make_header_row(), NORMAL_WIDTHS.to_vec()) };
+    // Viewport height (subtract 1 for header row)
+    let viewport_height = area.height.saturating_sub(1) as usize;
+
+    // Build only visible rows
+    let (rows, offset) = build_viewport_rows(app_state, is_left, viewport_height);
+    let state = if is_left { &app_state.state_left } else { &app_state.state_right };
+    let mut state_view = TableState::default();
+    state_view.select(state.selected().map(|s| s.saturating_sub(offset)));
 
-    let table_right = Table::new(rows_right, widths)
-        .block(Block::default().borders(Borders::RIGHT).border_style(STYLE_BORDER))
+    let table = Table::new(rows, widths)
+        .block(block)
         .header(header)
-        .row_highlight_style(table_style(!app_state.is_left_active))
+        .row_highlight_style(highlight)
         .column_spacing(1);
-    f.render_stateful_widget(table_right, chunks[2], &mut state_right_view);
+    f.render_stateful_widget(table, area, &mut state_view);
+}
+
+/// The inactive panel in quick view: highlighted text head, directory summary or hex dump.
+fn render_quick_view(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState, block: Block<'static>) {
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let height = inner.height as usize;
+
+    let lines: Vec<Line> = match &app_state.quick_view.content {
+        _ if app_state.quick_view.path.is_none() => Vec::new(),
+        None => vec![Line::from(Span::styled("Loading...", STYLE_COLUMNS))],
+        Some(QuickViewContent::Text(lines)) => lines.iter().take(height).map(|spans| Line::from(spans.clone())).collect(),
+        Some(QuickViewContent::Hex(lines)) => lines.iter().take(height).map(|line| Line::from(Span::styled(line.clone(), STYLE_FILE))).collect(),
+        Some(QuickViewContent::Error(message)) => vec![Line::from(Span::styled(message.clone(), STYLE_COLUMNS))],
+        Some(QuickViewContent::Dir(summary)) => {
+            let row = |label: &str, value: String| Line::from(vec![Span::styled(format!("{:<12}", label), STYLE_COLUMNS), Span::styled(value, STYLE_FILE)]);
+            let mut lines = vec![
+                row("Folders", summary.dirs.to_string()),
+                row("Files", summary.files.to_string()),
+                row("Total size", format!("{} ({} bytes)", format_size(summary.total_size), summary.total_size)),
+            ];
+            if let Some((name, modified)) = &summary.newest {
+                lines.push(row("Newest", name.clone()));
+                lines.push(row("", format_time(*modified)));
+            }
+            if summary.unreadable > 0 {
+                lines.push(row("Unreadable", format!("{} folders", summary.unreadable)));
+            }
+            if !summary.complete {
+                lines.push(Line::from(Span::styled("Scanning...", STYLE_COLUMNS)));
+            }
+            lines
+        }
+    };
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Header and widths for full mode: the icon, then the enabled columns in order.
+/// No separator cells, so more columns fit in half a screen.
+fn full_view_layout(view: &PanelView) -> (Row<'static>, Vec<Constraint>) {
+    let mut header = vec![Cell::from("")];
+    let mut widths = vec![Constraint::Length(2)];
+    for setting in view.enabled_columns() {
+        header.push(Cell::from(Span::styled(setting.column.title(), STYLE_COLUMNS)));
+        // Name takes whatever room is left
+        widths.push(if setting.column == Column::Name { Constraint::Min(setting.width) } else { Constraint::Length(setting.width) });
+    }
+    (Row::new(header), widths)
+}
+
+/// Names only, filling columns top to bottom; pages through the listing a screen at a time.
+fn render_brief_panel(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState, is_left: bool, block: Block<'static>, highlight: Style) {
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let children = if is_left { &app_state.children_left } else { &app_state.children_right };
+    let state = if is_left { &app_state.state_left } else { &app_state.state_right };
+    let selected_set = if is_left { &app_state.selected_left } else { &app_state.selected_right };
+    let compare_flags = if is_left { &app_state.compare_left } else { &app_state.compare_right };
+    let height = inner.height as usize;
+    if height == 0 || children.is_empty() {
+        return;
+    }
+
+    let columns = (inner.width / BRIEF_COLUMN_WIDTH).max(1);
+    let column_width = inner.width / columns;
+    let per_page = height * columns as usize;
+    let selected = state.selected().unwrap_or(0);
+    let start = selected / per_page * per_page;
+    let is_active = app_state.is_left_active == is_left;
+    let is_searching = is_active && !app_state.search_input.is_empty();
+    let is_renaming = is_active && app_state.is_f2_displayed;
+
+    for column in 0..columns {
+        let first = start + column as usize * height;
+        let lines: Vec<Line> = children.iter().enumerate().skip(first).take(height)
+            .map(|(index, child)| {
+                let text_color = if selected_set.contains(&index) { COLOR_SELECTED_MARKER } else { item_color(child) };
+                let text_style = Style::default().fg(text_color);
+                let (dir_prefix, dir_suffix) = if child.is_dir { ("[", "]") } else { ("", "") };
+
+                let mut spans = vec![row_icon(child, compare_flags, text_style), Span::styled(dir_prefix, text_style)];
+                if is_renaming && index == selected {
+                    spans.extend(app_state.rename_input.cursor_spans(text_style, text_style.add_modifier(Modifier::REVERSED)));
+                } else {
+                    let positions = is_searching.then(|| app_state.search_match(child.file_name())).flatten().map(|(_, positions)| positions).unwrap_or_default();
+                    spans.extend(highlight_chars(child.file_name(), &positions, text_style, text_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED)));
+                }
+                spans.push(Span::styled(dir_suffix, text_style));
+
+                // Pad so the cursor bar spans the whole column
+                let used: usize = spans.iter().map(|span| span.content.chars().count()).sum();
+                spans.push(Span::raw(" ".repeat((column_width as usize).saturating_sub(used + 1))));
+                let line = Line::from(spans);
+                if index == selected { line.style(highlight) } else { line }
+            })
+            .collect();
+        let rect = Rect::new(inner.x + column * column_width, inner.y, column_width.saturating_sub(1), inner.height);
+        f.render_widget(Paragraph::new(lines), rect);
+    }
+}
+
+/// ncdu-style listing of the scanned directory, biggest first with a share bar.
+/// Returns the list height for paging.
+fn render_disk_usage(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) -> u16 {
+    const BAR_WIDTH: usize = 20;
+    let (dir, is_scanning) = match (&app_state.du_view, &app_state.du_scan) {
+        (Some(view), _) => (view.dir.as_path(), false),
+        (None, Some(scan)) => (scan.root.as_path(), true),
+        (None, None) => return 0,
+    };
+    let title = format!(" Disk Usage: {} ", limit_path_string(dir, (area.width as usize).saturating_sub(20)));
+    let block = Block::default()
+        .title(Line::from(Span::styled(title, STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .border_style(STYLE_BORDER);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let Some(view) = app_state.du_view.as_ref().filter(|_| !is_scanning) else {
+        if let Some(scan) = &app_state.du_scan {
+            let progress = format!("Scanning... {} directories, {} on disk", scan.dirs_scanned, format_size(scan.bytes_scanned));
+            f.render_widget(Paragraph::new(progress).alignment(Alignment::Center).style(STYLE_TITLE), chunks[1].inner(Margin { vertical: chunks[1].height / 2, horizontal: 0 }));
+        }
+        f.render_widget(Paragraph::new("Esc - Cancel").alignment(Alignment::Center).style(STYLE_COLUMNS), chunks[2]);
+        return 0;
+    };
+
+    let total = view.tree.total(&view.dir);
+    let age = view.tree.scanned_at.elapsed().as_secs();
+    let age = if age < 60 { "just now".to_string() } else if age < 3600 { format!("{}m ago", age / 60) } else { format!("{}h ago", age / 3600) };
+    let unreadable = if view.tree.unreadable > 0 { format!(", {} unreadable", view.tree.unreadable) } else { String::new() };
+    let sort = if view.is_apparent { "apparent size" } else { "disk usage" };
+    let summary = format!("{} on disk, {} apparent, {} files{} - sorted by {} - scanned {}", format_size(total.disk), format_size(total.apparent), total.files, unreadable, sort, age);
+    f.render_widget(Paragraph::new(summary).style(STYLE_COLUMNS), chunks[0]);
+
+    let height = chunks[1].height as usize;
+    let start = view.selected.saturating_sub(height / 2).min(view.rows.len().saturating_sub(height));
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+    let total_size = total.size(view.is_apparent).max(1);
+    let lines: Vec<Line> = view.rows.iter().enumerate().skip(start).take(height)
+        .map(|(index, row)| {
+            let share = row.usage.size(view.is_apparent) as f64 / total_size as f64;
+            let filled = ((share * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+            let name_style = if row.is_dir { STYLE_DIR } else { Style::default().fg(color_for_extension(Path::new(&row.name).extension().and_then(|ext| ext.to_str()).unwrap_or(""))) };
+            let name = if row.is_dir { format!("[{}]", row.name) } else { row.name.clone() };
+            let line = Line::from(vec![
+                Span::styled(format!("{:>10} {:>10} ", format_size(row.usage.disk), format_size(row.usage.apparent)), STYLE_FILE),
+                Span::styled("█".repeat(filled), STYLE_TITLE),
+                Span::styled("░".repeat(BAR_WIDTH - filled), STYLE_BORDER),
+                Span::styled(format!(" {:>5.1}% ", share * 100.0), STYLE_COLUMNS),
+                Span::styled(name, name_style),
+            ]);
+            if index == view.selected { line.style(selected_style) } else { line }
+        })
+        .collect();
+    if lines.is_empty() {
+        f.render_widget(Paragraph::new("Empty directory").alignment(Alignment::Center).style(STYLE_DIR_DARK), chunks[1]);
+    } else {
+        f.render_widget(Paragraph::new(lines), chunks[1]);
+    }
+
+    let footer = match view.selected_row().filter(|_| view.is_delete_prompt) {
+        Some(row) => Paragraph::new(format!("Delete \"{}\"? Y / Enter - Yes    N / Esc - No", row.name)).style(Style::default().fg(COLOR_SELECTED_MARKER)),
+        None => Paragraph::new("Enter/Right - Open    Left - Up    a - Apparent/disk    r - Rescan    F8 - Delete    Esc - Close").style(STYLE_COLUMNS),
+    };
+    f.render_widget(footer.alignment(Alignment::Center), chunks[2]);
+    chunks[1].height
+}
+
+/// Ranger-style layout: parent directory, active panel, preview of the cursor row.
+fn render_miller_columns(f: &mut ratatui::Frame<'_>, chunk: Rect, app_state: &mut AppState) -> u16 {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(20), Constraint::Percentage(40), Constraint::Percentage(40)])
+        .split(chunk);
+    let is_left = app_state.is_left_active;
+    // Mouse clicks hit-test against the middle column only, as the active panel
+    let empty = Rect::default();
+    let (left, right) = if is_left { (chunks[1], empty) } else { (empty, chunks[1]) };
+    app_state.panel_layout = PanelLayout { area: chunk, left, separator: empty, right };
+
+    // Parent column, with the current directory highlighted
+    let parent_block = Block::default().borders(Borders::LEFT | Borders::RIGHT).border_style(STYLE_BORDER);
+    let parent_area = parent_block.inner(chunks[0]);
+    f.render_widget(parent_block, chunks[0]);
+    if let Some(parent) = &app_state.miller_parent {
+        let current_dir = if is_left { &app_state.dir_left } else { &app_state.dir_right };
+        let selected = match &parent.content {
+            Some(PreviewContent::Dir(items)) => items.iter().position(|item| item.path == *current_dir),
+            _ => None,
+        };
+        let highlight = Style::default().bg(COLOR_SELECTED_BACKGROUND_INACTIVE).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+        f.render_widget(Paragraph::new(preview_lines(parent.content.as_ref(), parent_area.height as usize, selected, highlight)), parent_area);
+    }
+
+    // Current directory, drawn like a dual-pane panel
+    let highlight = Style::default().bg(if app_state.is_f2_displayed { COLOR_RENAME_BACKGROUND } else { COLOR_SELECTED_BACKGROUND }).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+    render_panel(f, chunks[1], app_state, is_left, Block::default(), highlight);
+
+    // Preview of the row under the cursor
+    let preview_block = Block::default().borders(Borders::LEFT | Borders::RIGHT).border_style(STYLE_BORDER);
+    let preview_area = preview_block.inner(chunks[2]);
+    f.render_widget(preview_block, chunks[2]);
+    if let Some(preview) = &app_state.miller_preview {
+        f.render_widget(Paragraph::new(preview_lines(preview.content.as_ref(), preview_area.height as usize, None, Style::default())), preview_area);
+    }
+
+    chunks[1].height
+}
 
-    chunks[0].height
+/// Lines for a preview pane; `selected` marks one directory entry (kept in view) with `highlight`.
+fn preview_lines(content: Option<&PreviewContent>, height: usize, selected: Option<usize>, highlight: Style) -> Vec<Line<'static>> {
+    let Some(content) = content else { return vec![Line::from(Span::styled("Loading...", STYLE_COLUMNS))] };
+    match content {
+        PreviewContent::Dir(items) if items.is_empty() => vec![Line::from(Span::styled("(empty)", STYLE_COLUMNS))],
+        PreviewContent::Dir(items) => {
+            let start = selected.map_or(0, |index| index.saturating_sub(height / 2).min(items.len().saturating_sub(height)));
+            items.iter().enumerate().skip(start).take(height)
+                .map(|(index, item)| {
+                    let text = if item.is_dir { format!("[{}]", item.name_full) } else { item.name_full.clone() };
+                    let color = item_color(item);
+                    let style = if Some(index) == selected { highlight } else { Style::default().fg(color) };
+                    Line::from(Span::styled(text, style))
+                })
+                .collect()
+        }
+        PreviewContent::Text(lines) => lines.iter().take(height).map(|line| Line::from(Span::styled(line.replace('\t', TAB_SPACES), STYLE_FILE))).collect(),
+        PreviewContent::Binary => vec![Line::from(Span::styled("Binary file", STYLE_COLUMNS))],
+        PreviewContent::Error(message) => vec![Line::from(Span::styled(message.clone(), STYLE_COLUMNS))],
+    }
 }
 
 /// Build only the rows visible in the viewport, returns (rows, start_offset)
@@ -179,7 +572,6 @@ fn build_viewport_rows(app_state: &AppState, is_left: bool, viewport_height: usi
     let children = if is_left { &app_state.children_left } else { &app_state.children_right };
     let state = if is_left { &app_state.state_left } else { &app_state.state_right };
     let selected_set = if is_left { &app_state.selected_left } else { &app_state.selected_right };
-    let current_dir = if is_left { &app_state.dir_left } else { &app_state.dir_right };
     let selected = state.selected().unwrap_or(0);
     let total = children.len();
 
@@ -200,24 +592,20 @@ fn build_viewport_rows(app_state: &AppState, is_left: bool, viewport_height: usi
 
     let is_renaming_current_side = app_state.is_f2_displayed && (app_state.is_left_active == is_left);
     let border_cell = Cell::from(Span::styled("│", STYLE_BORDER));
+    let is_searching = app_state.is_left_active == is_left && !app_state.search_input.is_empty();
+    let is_list = if is_left { app_state.list_left.is_some() } else { app_state.list_right.is_some() };
+    let (is_tree, expanded) = if is_left { (app_state.tree_left, &app_state.expanded_left) } else { (app_state.tree_right, &app_state.expanded_right) };
+    let mut guides = if is_tree { tree_guides(children, start, end) } else { Vec::new() };
+    let view = if is_left { &app_state.view_left } else { &app_state.view_right };
+    let compare_flags = if is_left { &app_state.compare_left } else { &app_state.compare_right };
 
     let mut rows = Vec::with_capacity(end - start);
 
-    for index in start..end {
-        let child = &children[index];
+    for (index, child) in children.iter().enumerate().take(end).skip(start) {
         let is_renaming_current_item = is_renaming_current_side && (index == selected);
         let is_selected = selected_set.contains(&index);
 
-        // Keep original icon, change color if selected
-        let icon = if child.is_dir { ICON_FOLDER } else { ICON_FILE };
-        let file_color = color_for_extension(&child.extension);
-        let text_color = if is_selected {
-            COLOR_SELECTED_MARKER
-        } else if child.is_dir {
-            COLOR_DIRECTORY
-        } else {
-            file_color
-        };
+        let text_color = if is_selected { COLOR_SELECTED_MARKER } else { item_color(child) };
         let text_style = Style::default().fg(text_color);
 
         let (dir_prefix, dir_suffix) = if child.is_dir { ("[", "]") } else { ("", "") };
@@ -234,41 +622,126 @@ fn build_viewport_rows(app_state: &AppState, is_left: bool, viewport_height: usi
             let mut spans = vec![Span::styled(dir_prefix, bracket_style)];
             spans.extend(app_state.rename_input.cursor_spans(text_style, cursor_style));
             spans.push(Span::styled(dir_suffix, bracket_style));
-            (Cell::from(Line::from(spans)), String::new())
+            (Cell::from(Line::from(spans)), Vec::new())
         } else {
-            (Cell::from(Line::from(vec![
-                Span::styled(dir_prefix, bracket_style),
-                Span::styled(child.name.clone(), text_style),
-                Span::styled(dir_suffix, bracket_style),
-            ])), child.extension.clone())
+            // Quick search hits are emphasized in the name and extension
+            let file_name = child.file_name();
+            let positions = is_searching.then(|| app_state.search_match(file_name)).flatten().map(|(_, positions)| positions).unwrap_or_default();
+            // List rows show their path relative to the panel directory
+            let display_name = match (view.shows_extension_in_name(), is_list) {
+                (true, true) => child.name_full.as_str(),
+                (true, false) => file_name,
+                (false, _) => child.name.as_str(),
+            };
+            // Search positions index the file name, which ends the displayed name
+            let stem_len = file_name.chars().count() - if child.extension.is_empty() { 0 } else { child.extension.chars().count() + 1 };
+            let shown_len = if view.shows_extension_in_name() { file_name.chars().count() } else { stem_len };
+            let prefix_len = display_name.chars().count().saturating_sub(shown_len);
+            let name_positions: Vec<usize> = positions.iter().map(|&pos| pos + prefix_len).collect();
+            let matched_style = text_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            let mut spans = Vec::new();
+            if is_tree {
+                let marker = if !child.is_dir || child.name == ".." {
+                    "  "
+                } else if expanded.contains(&child.path) {
+                    "▾ "
+                } else {
+                    "▸ "
+                };
+                spans.push(Span::styled(std::mem::take(&mut guides[index - start]), STYLE_BORDER));
+                spans.push(Span::styled(marker, bracket_style));
+            }
+            spans.push(Span::styled(dir_prefix, bracket_style));
+            spans.extend(highlight_chars(display_name, &name_positions, text_style, matched_style));
+            spans.push(Span::styled(dir_suffix, bracket_style));
+            let extension_positions: Vec<usize> = positions.iter().filter_map(|&pos| pos.checked_sub(stem_len + 1)).collect();
+            (Cell::from(Line::from(spans)), highlight_chars(&child.extension, &extension_positions, text_style, matched_style))
         };
 
-        // Get size - for directories, show calculated size if available
-        let size = if child.is_dir && child.name != ".." {
-            if let Some(&calculated_size) = app_state.dir_sizes.get(&current_dir.join(&child.name_full)) {
-                format_size(calculated_size)
-            } else {
-                child.size.clone()
+        // For directories, show calculated size if available
+        let dir_size = if child.is_dir && child.name != ".." { app_state.dir_sizes.get(&child.path).copied() } else { None };
+        let icon_cell = Cell::from(row_icon(child, compare_flags, Style::default().fg(text_color)));
+
+        if view.mode == ViewMode::Full {
+            let mut cells = vec![icon_cell];
+            for setting in view.enabled_columns() {
+                cells.push(match setting.column {
+                    Column::Name => name_cell.clone(),
+                    Column::Ext => Cell::from(Line::from(extension.clone())),
+                    column => Cell::from(Span::styled(column.cell_text(child, dir_size), text_style)),
+                });
             }
-        } else {
-            child.size.clone()
-        };
+            rows.push(Row::new(cells));
+            continue;
+        }
 
+        let size = child.size_text(dir_size);
         rows.push(Row::new(vec![
-            Cell::from(Span::styled(icon, Style::default().fg(text_color))),
+            icon_cell,
             name_cell,
             border_cell.clone(),
-            Cell::from(Span::styled(extension, text_style)),
+            Cell::from(Line::from(extension)),
             border_cell.clone(),
             Cell::from(Span::styled(size, text_style)),
             border_cell.clone(),
-            Cell::from(Span::styled(child.modified.clone(), text_style)),
+            Cell::from(Span::styled(format_time_opt(child.modified), text_style)),
         ]));
     }
 
     (rows, start)
 }
 
+/// Row color by entry type: directories, special files, then files by extension.
+fn item_color(item: &Item) -> Color {
+    if item.is_dir {
+        COLOR_DIRECTORY
+    } else if item.details.kind.is_special() {
+        COLOR_SPECIAL
+    } else {
+        color_for_extension(&item.extension)
+    }
+}
+
+/// The file/folder icon (colored like the row, so it follows selection),
+/// or the compare marker when the last comparison flagged the entry.
+fn row_icon(child: &Item, compare_flags: &HashMap<PathBuf, CompareFlag>, style: Style) -> Span<'static> {
+    match compare_flags.get(&child.path) {
+        Some(flag) => Span::styled(flag.symbol(), Style::default().fg(COLOR_SELECTED_MARKER).add_modifier(Modifier::BOLD)),
+        None if child.error.is_some() => Span::styled("✗ ", Style::default().fg(COLOR_RENAME_BACKGROUND).add_modifier(Modifier::BOLD)),
+        None => Span::styled(if child.is_dir { ICON_FOLDER } else { ICON_FILE }, style),
+    }
+}
+
+/// Indentation guides (`│ `, `├─`, `└─`) for the tree rows `start..end`, built in one backward pass over them.
+fn tree_guides(children: &[Item], start: usize, end: usize) -> Vec<String> {
+    let mut guides = vec![String::new(); end - start];
+    // has_next[depth]: a later sibling exists at that depth before the subtree ends
+    let mut has_next: Vec<bool> = Vec::new();
+    // Rows below the viewport only matter until a row shallow enough to end every open level
+    let mut shallowest = usize::MAX;
+    for item in &children[end..] {
+        if item.depth < shallowest {
+            shallowest = item.depth;
+            has_next.resize(has_next.len().max(shallowest + 1), false);
+            has_next[shallowest] = true;
+        }
+        if shallowest <= 1 {
+            break;
+        }
+    }
+    for (index, item) in children[start..end].iter().enumerate().rev() {
+        let depth = item.depth;
+        has_next.resize(depth + 1, false);
+        if depth > 0 {
+            let mut guide: String = (1..depth).map(|level| if has_next[level] { "│ " } else { "  " }).collect();
+            guide.push_str(if has_next[depth] { "├─" } else { "└─" });
+            guides[index] = guide;
+        }
+        has_next[depth] = true;
+    }
+    guides
+}
+
 fn make_header_row() -> Row<'static> {
     Row::new(vec![
         Cell::from(Span::styled("", STYLE_COLUMNS)),
@@ -282,13 +755,12 @@ fn make_header_row() -> Row<'static> {
     ])
 }
 
-fn render_viewer(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) -> usize {
-    if let Some(viewer_state) = &app_state.viewer_state {
-        let filename = viewer_state.file_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Unknown");
-        let prefix = if viewer_state.from_edit { "Edit" } else { "View" };
-        let title = format!(" {}: {} ", prefix, filename);
+fn render_viewer(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &mut AppState) -> usize {
+    if let Some(viewer_state) = &mut app_state.viewer_state {
+        let filename = file_name_lossy(&viewer_state.file_path);
+        let prefix = if viewer_state.from_edit || viewer_state.hex_edit.is_some() { "Edit" } else { "View" };
+        let modified = if viewer_state.is_hex_modified() { " [Modified]" } else { "" };
+        let title = format!(" {}: {}{} ", prefix, filename, modified);
 
         let border_block = Block::default()
             .title(Line::from(Span::styled(title, STYLE_TITLE)).centered())
@@ -298,17 +770,43 @@ fn render_viewer(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) -
         let inner_area = border_block.inner(area);
         f.render_widget(border_block, area);
 
-        // Calculate line number gutter width
-        let line_num_width = (viewer_state.total_lines.to_string().len() as u16).max(3) + 2;
+        let viewport_height = inner_area.height as usize;
+        let start = viewer_state.scroll_offset;
+        if viewer_state.mode == ViewerMode::Hex {
+            // The offset column takes the place of line numbers
+            let content_lines: Vec<Line> = match viewer_state.hex_bytes(start, viewport_height).map(|bytes| bytes.to_vec()) {
+                Ok(bytes) => bytes.chunks(HEX_ROW_BYTES as usize).enumerate().map(|(row, chunk)| hex_row_line((start + row) as u64 * HEX_ROW_BYTES, chunk, viewer_state.hex_edit.as_ref())).collect(),
+                Err(e) => vec![Line::from(Span::styled(format!("Cannot read file: {}", e), STYLE_TITLE))],
+            };
+            f.render_widget(Paragraph::new(content_lines).style(STYLE_FILE).scroll((0, viewer_state.horizontal_offset as u16)), inner_area);
+            return viewport_height;
+        }
+
+        let lines = match viewer_state.text_lines(start, viewport_height).map(|lines| lines.to_vec()) {
+            Ok(lines) => lines,
+            Err(e) => {
+                f.render_widget(Paragraph::new(format!("Cannot read file: {}", e)).style(STYLE_TITLE), inner_area);
+                return viewport_height;
+            }
+        };
+
+        if lines.is_empty() && viewer_state.approx_lines().is_some() {
+            // Scrolled past what the index has reached, shown once it gets there
+            let message = format!("Indexing… line {} not reached yet ({} lines so far)", start + 1, viewer_state.total_lines);
+            f.render_widget(Paragraph::new(message).style(STYLE_COLUMNS), inner_area);
+            return viewport_height;
+        }
+
+        // Calculate line number gutter width, wide enough for the estimate while indexing
+        let line_count = viewer_state.approx_lines().unwrap_or(0).max(viewer_state.total_lines);
+        let line_num_width = (line_count.to_string().len() as u16).max(3) + 2;
 
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Length(line_num_width), Constraint::Min(0)])
             .split(inner_area);
 
-        let viewport_height = inner_area.height as usize;
-        let start = viewer_state.scroll_offset;
-        let end = (start + viewport_height).min(viewer_state.total_lines);
+        let end = start + lines.len();
         let num_width = line_num_width as usize - 1;
 
         // Render line numbers
@@ -323,23 +821,28 @@ fn render_viewer(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) -
         f.render_widget(line_number_para, chunks[0]);
 
         // Render content
-        if viewer_state.is_binary {
-            let binary_msg = Paragraph::new("Binary file detected. Press Esc to return.")
-                .alignment(Alignment::Center)
-                .style(STYLE_TITLE);
-            f.render_widget(binary_msg, chunks[1]);
-        } else {
-            let content_lines: Vec<Line> = viewer_state.content_lines[start..end]
-                .iter()
-                .map(|line| Line::from(Span::raw(line.replace('\t', TAB_SPACES))))
-                .collect();
-
-            let content_para =
-                Paragraph::new(content_lines)
-                    .style(STYLE_FILE)
-                    .scroll((0, viewer_state.horizontal_offset as u16));
-            f.render_widget(content_para, chunks[1]);
-        }
+        let highlight_style = Style::default().fg(COLOR_SELECTED_FOREGROUND).bg(COLOR_SELECTED_BACKGROUND).add_modifier(Modifier::BOLD);
+        let content_lines: Vec<Line> = lines
+            .iter()
+            .enumerate()
+            .map(|(offset, line)| match &viewer_state.highlight {
+                Some((highlight_line, range)) if *highlight_line == start + offset => {
+                    let (before, matched, after) = split_at_range(line, range);
+                    Line::from(vec![
+                        Span::raw(before.replace('\t', TAB_SPACES)),
+                        Span::styled(matched.replace('\t', TAB_SPACES), highlight_style),
+                        Span::raw(after.replace('\t', TAB_SPACES)),
+                    ])
+                }
+                _ => Line::from(Span::raw(line.replace('\t', TAB_SPACES))),
+            })
+            .collect();
+
+        let content_para =
+            Paragraph::new(content_lines)
+                .style(STYLE_FILE)
+                .scroll((0, viewer_state.horizontal_offset as u16));
+        f.render_widget(content_para, chunks[1]);
 
         viewport_height
     } else {
@@ -347,11 +850,40 @@ fn render_viewer(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) -
     }
 }
 
+/// One hex dump row laid out like `hex_dump`, with edited bytes and the hex editor cursor marked.
+fn hex_row_line(offset: u64, bytes: &[u8], edit: Option<&HexEdit>) -> Line<'static> {
+    let modified_style = Style::default().fg(COLOR_SELECTED_MARKER).add_modifier(Modifier::BOLD);
+    let mut hex = vec![Span::raw(format!("{:08x}  ", offset))];
+    let mut ascii = vec![Span::raw("|")];
+    for column in 0..HEX_ROW_BYTES as usize {
+        let Some(&original) = bytes.get(column) else {
+            hex.push(Span::raw(if column == 7 { "    " } else { "   " }));
+            continue;
+        };
+        let byte_offset = offset + column as u64;
+        let changed = edit.and_then(|edit| edit.changes.get(&byte_offset)).copied();
+        let value = changed.unwrap_or(original);
+        let style = if changed.is_some() { modified_style } else { Style::default() };
+        let (hex_style, ascii_style) = match edit.filter(|edit| edit.cursor == byte_offset) {
+            // The column being typed into gets the block cursor, the other one an underline
+            Some(edit) if edit.is_ascii => (style.add_modifier(Modifier::UNDERLINED), style.add_modifier(Modifier::REVERSED)),
+            Some(_) => (style.add_modifier(Modifier::REVERSED), style.add_modifier(Modifier::UNDERLINED)),
+            None => (style, style),
+        };
+        hex.push(Span::styled(format!("{:02x}", value), hex_style));
+        hex.push(Span::raw(if column == 7 { "  " } else { " " }));
+        let shown = if value.is_ascii_graphic() || value == b' ' { value as char } else { '.' };
+        ascii.push(Span::styled(shown.to_string(), ascii_style));
+    }
+    ascii.push(Span::raw("|"));
+    hex.push(Span::raw(" "));
+    hex.extend(ascii);
+    Line::from(hex)
+}
+
 fn render_editor(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &mut AppState) -> usize {
     if let Some(editor_state) = &mut app_state.editor_state {
-        let filename = editor_state.file_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Unknown");
+        let filename = file_name_lossy(&editor_state.file_path);
         let modified = if editor_state.modified { " [Modified]" } else { "" };
         let title = format!(" Edit: {}{} ", filename, modified);
 
@@ -498,9 +1030,7 @@ fn render_bottom_panel(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppSt
     if app_state.is_f4_displayed {
         // Show editor status
         if let Some(editor_state) = &app_state.editor_state {
-            let filename = editor_state.file_path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown");
+            let filename = file_name_lossy(&editor_state.file_path);
             let modified = if editor_state.modified { " [Modified]" } else { "" };
             let name_seg = format!("{}{}", filename, modified);
             let pos_seg = format!("Ln {}, Col {}", editor_state.cursor_line + 1, editor_state.cursor_col + 1);
@@ -510,72 +1040,123 @@ fn render_bottom_panel(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppSt
     } else if app_state.is_f3_displayed {
         // Show viewer status
         if let Some(viewer_state) = &app_state.viewer_state {
-            let filename = viewer_state.file_path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown");
-            let line_seg = format!("Line {}/{}", viewer_state.scroll_offset + 1, viewer_state.total_lines);
+            if app_state.is_viewer_goto_displayed {
+                let label = if viewer_state.mode == ViewerMode::Hex { " Go to offset (0x hex or decimal): " } else { " Go to line: " };
+                let mut spans = vec![Span::styled("├─", STYLE_BORDER), Span::styled(label, status_style)];
+                spans.extend(app_state.viewer_goto_input.cursor_spans(status_style, status_style.add_modifier(Modifier::REVERSED)));
+                f.render_widget(Paragraph::new(Line::from(spans)), area);
+                return;
+            }
+            let filename = file_name_lossy(&viewer_state.file_path);
+            if let Some(edit) = &viewer_state.hex_edit {
+                let modified = if edit.changes.is_empty() { String::new() } else { format!(" [{} modified]", edit.changes.len()) };
+                let name_seg = format!("{}{}", filename, modified);
+                let offset_seg = format!("Offset {:08x}", edit.cursor);
+                let column_seg = if edit.is_ascii { "Tab Hex" } else { "Tab ASCII" };
+                render_segmented_status_bar(f, area, &[&name_seg, &offset_seg, column_seg, "Ctrl+Z Undo", "F2/Ctrl+S Save", "Esc Exit"]);
+                return;
+            }
+            let (position_seg, mode_seg) = match viewer_state.mode {
+                ViewerMode::Text => match viewer_state.approx_lines() {
+                    Some(approx) => (format!("Line {}/~{} approx", viewer_state.scroll_offset + 1, approx), "F4 Hex"),
+                    None => (format!("Line {}/{}", viewer_state.scroll_offset + 1, viewer_state.total_lines), "F4 Hex"),
+                },
+                ViewerMode::Hex => (format!("Offset {:08x}", viewer_state.scroll_offset as u64 * HEX_ROW_BYTES), "F4 Text"),
+            };
             let size_seg = format_size(viewer_state.file_size);
-            render_segmented_status_bar(f, area, &[filename, &line_seg, &size_seg, &viewer_state.syntax_name]);
+            render_segmented_status_bar(f, area, &[&filename, &position_seg, &size_seg, &viewer_state.syntax_name, "F2 Edit", mode_seg, "F5 Go to"]);
         }
     } else if !app_state.search_input.is_empty() {
         // Show search string
-        let text = format!(" Search: {} ", app_state.search_input);
+        let filter = if app_state.is_search_filter { ", filter" } else { "" };
+        let text = format!(" Search ({}{}): {} ", app_state.search_mode.label(), filter, app_state.search_input);
         render_status_bar(f, area, text, status_style);
+    } else if let Some((name, error)) = app_state.cursor_item().and_then(|item| item.error.as_ref().map(|error| (&item.name_full, error))) {
+        // Why the row under the cursor could not be read
+        render_status_bar(f, area, format!(" {}: {} ", name, error), status_style);
     } else {
         // Show panel stats: selected/total files and selected/total size
         // Returns (count_part, size_part) e.g. ("0/5", "1.2 KiB") or ("2/5", "800 B/1.2 KiB")
-        let panel_stat = |children: &[crate::app::Item], selected_set: &std::collections::HashSet<usize>, current_dir: &PathBuf, dir_sizes: &std::collections::HashMap<PathBuf, u64>| -> (String, String) {
+        // With an active filter the count reads "selected/shown of unfiltered"
+        let panel_stat = |children: &[crate::app::Item], selected_set: &std::collections::HashSet<usize>, dir_sizes: &std::collections::HashMap<PathBuf, u64>, filter: &PanelFilter, unfiltered: usize| -> (String, String) {
             let item_size = |c: &crate::app::Item| -> u64 {
                 if c.is_dir {
-                    dir_sizes.get(&current_dir.join(&c.name_full)).copied().unwrap_or(0)
+                    dir_sizes.get(&c.path).copied().unwrap_or(0)
                 } else {
                     c.size_bytes
                 }
             };
             let total_count = children.iter().filter(|c| c.name != "..").count();
-            let total_size: u64 = children.iter().filter(|c| c.name != "..").map(|c| item_size(c)).sum();
+            let total_size: u64 = children.iter().filter(|c| c.name != "..").map(&item_size).sum();
+            let total_label = if filter.is_active() { format!("{} of {}", total_count, unfiltered) } else { total_count.to_string() };
 
             if selected_set.is_empty() {
-                (format!("0/{}", total_count), format_size(total_size))
+                (format!("0/{}", total_label), format_size(total_size))
             } else {
-                let sel_count = selected_set.iter().filter(|&&idx| children.get(idx).map_or(false, |c| c.name != "..")).count();
-                let sel_size: u64 = selected_set.iter().filter_map(|&idx| children.get(idx)).filter(|c| c.name != "..").map(|c| item_size(c)).sum();
-                (format!("{}/{}", sel_count, total_count), format!("{}/{}", format_size(sel_size), format_size(total_size)))
+                let sel_count = selected_set.iter().filter(|&&idx| children.get(idx).is_some_and(|c| c.name != "..")).count();
+                let sel_size: u64 = selected_set.iter().filter_map(|&idx| children.get(idx)).filter(|c| c.name != "..").map(item_size).sum();
+                (format!("{}/{}", sel_count, total_label), format!("{}/{}", format_size(sel_size), format_size(total_size)))
             }
         };
 
-        let (left_count, left_size) = panel_stat(&app_state.children_left, &app_state.selected_left, &app_state.dir_left, &app_state.dir_sizes);
-        let (right_count, right_size) = panel_stat(&app_state.children_right, &app_state.selected_right, &app_state.dir_right, &app_state.dir_sizes);
-
-        // " count - size " → len = 1 + count + 3 + size + 1
-        let left_stat_len = 1 + left_count.len() + 3 + left_size.len() + 1;
-        let right_stat_len = 1 + right_count.len() + 3 + right_size.len() + 1;
+        // A listing still streaming in shows how far it got instead of the count
+        let loading_label = |load: &Option<DirLoad>| load.as_ref().filter(|load| !load.is_refresh).map(|load| format!("loading {} entries...", load.entries));
+        let (left_count, left_size) = panel_stat(&app_state.children_left, &app_state.selected_left, &app_state.dir_sizes, &app_state.filter_left, app_state.total_left);
+        let (right_count, right_size) = panel_stat(&app_state.children_right, &app_state.selected_right, &app_state.dir_sizes, &app_state.filter_right, app_state.total_right);
+        let left_count = loading_label(&app_state.load_left).unwrap_or(left_count);
+        let right_count = loading_label(&app_state.load_right).unwrap_or(right_count);
 
         let total_width = area.width as usize;
-        let left_pad = (total_width.saturating_sub(3) / 2).saturating_sub(left_stat_len + 1);
-        let right_pad = (total_width.saturating_sub(2) / 2).saturating_sub(right_stat_len + 1);
+        if !app_state.is_miller && (app_state.is_zoomed || app_state.is_split_horizontal) {
+            // Stacked or zoomed panels: stats of the active panel only
+            let (count, size, space) = if app_state.is_left_active { (left_count, left_size, &app_state.space_left) } else { (right_count, right_size, &app_state.space_right) };
+            let mut status_line = vec![Span::styled("├─", STYLE_BORDER)];
+            status_line.extend(panel_stat_spans(count, size, space.info, total_width.saturating_sub(3), STYLE_TITLE));
+            status_line.push(Span::styled("┤", STYLE_BORDER));
+            f.render_widget(Paragraph::new(Line::from(status_line)), area);
+            return;
+        }
 
+        let separator = if app_state.is_miller { split_offset(area.width, 50) } else { split_offset(area.width, app_state.split_percent) } as usize;
         let (left_style, right_style) = if app_state.is_left_active {
             (STYLE_TITLE, STYLE_DIR_DARK)
         } else {
             (STYLE_DIR_DARK, STYLE_TITLE)
         };
 
-        let status_line = vec![
-            Span::styled("├─", STYLE_BORDER),
-            Span::styled(format!(" {}", left_count), left_style),
-            Span::styled(" - ", STYLE_BORDER),
-            Span::styled(format!("{} ", left_size), left_style),
-            Span::styled(format!("{}┴─", "─".repeat(left_pad)), STYLE_BORDER),
-            Span::styled(format!(" {}", right_count), right_style),
-            Span::styled(" - ", STYLE_BORDER),
-            Span::styled(format!("{} ", right_size), right_style),
-            Span::styled(format!("{}┤", "─".repeat(right_pad)), STYLE_BORDER),
-        ];
+        let mut status_line = vec![Span::styled("├─", STYLE_BORDER)];
+        status_line.extend(panel_stat_spans(left_count, left_size, app_state.space_left.info, separator.saturating_sub(2), left_style));
+        status_line.push(Span::styled("┴─", STYLE_BORDER));
+        status_line.extend(panel_stat_spans(right_count, right_size, app_state.space_right.info, total_width.saturating_sub(separator + 3), right_style));
+        status_line.push(Span::styled("┤", STYLE_BORDER));
         f.render_widget(Paragraph::new(Line::from(status_line)), area);
     }
 }
 
+/// One panel's part of the status line, `width` cells between the border junctions:
+/// " count - size " on the left and " free of total " on the right when it fits.
+fn panel_stat_spans(count: String, size: String, space: Option<SpaceInfo>, width: usize, style: Style) -> Vec<Span<'static>> {
+    // " count - size " → len = 1 + count + 3 + size + 1
+    let stat_len = 1 + count.chars().count() + 3 + size.chars().count() + 1;
+    let mut spans = vec![
+        Span::styled(format!(" {}", count), style),
+        Span::styled(" - ", STYLE_BORDER),
+        Span::styled(format!("{} ", size), style),
+    ];
+    let space_label = space.map(|space| (format!(" {} free of {} ", format_size(space.free), format_size(space.total)), space.free.saturating_mul(10) < space.total));
+    match space_label {
+        Some((label, is_low)) if stat_len + label.chars().count() + 2 <= width => {
+            let label_style = if is_low { Style::new().fg(COLOR_SELECTED_MARKER) } else { style };
+            let pad = width - stat_len - label.chars().count() - 1;
+            spans.push(Span::styled("─".repeat(pad), STYLE_BORDER));
+            spans.push(Span::styled(label, label_style));
+            spans.push(Span::styled("─", STYLE_BORDER));
+        }
+        _ => spans.push(Span::styled("─".repeat(width.saturating_sub(stat_len)), STYLE_BORDER)),
+    }
+    spans
+}
+
 fn render_fkey_bar(f: &mut ratatui::Frame<'_>, area: Rect) {
     let block_bottom = Block::default()
         .title_bottom(Line::from(Span::styled(" F1 Help ", STYLE_TITLE)).centered())
@@ -600,7 +1181,7 @@ fn render_error_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &mut Ap
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     f.render_widget(
@@ -613,8 +1194,8 @@ fn render_help_popup(f: &mut ratatui::Frame<'_>, area: Rect) {
     let help_lines = vec![
         "F1 - This help",
         "F2 - Rename folder/file",
-        "F3 - View file",
-        "F4 - Edit file (Ctrl+S/F2 save)",
+        "F3 - View file (F2 hex edit, F4 hex/text, F5 go to offset/line)",
+        "F4 - Edit file (Ctrl+S/F2 save, hex editor for binaries)",
         "F5 - Copy to other panel",
         "F6 - Move to other panel",
         "F7 - Create directory",
@@ -622,7 +1203,25 @@ fn render_help_popup(f: &mut ratatui::Frame<'_>, area: Rect) {
         "F9 - Open terminal",
         "F10 - Quit",
         "Space - Select/deselect file",
+        "Ctrl+H - Toggle hidden files",
+        "Ctrl+F - Panel filter mask",
+        "Alt+F7 - Find file / text in files",
+        "Ctrl+P - Fuzzy go to file",
+        "Alt+P - Panelize command output or <file",
+        "Alt+D - Compare directories",
+        "Alt+Y - Synchronize directories",
+        "Alt+U - Disk usage analyzer",
+        "Alt+F1/F2 - Mounts for left/right panel",
+        "Alt+T - Tree view (Left/Right collapse/expand)",
+        "Alt+M - Miller columns (Left/Right navigate)",
+        "Ctrl+Q - Quick view in the other panel",
+        "Ctrl+Left/Right - Resize panel split",
+        "Alt+Z - Zoom active panel, Alt+S - Stack panels",
+        "Alt+V - View mode (normal/brief/full)",
+        "Alt+C - Choose full view columns",
         "Type to search, Esc to clear",
+        "Ctrl+S - Search mode (prefix/substring/fuzzy/glob)",
+        "Ctrl+T - Search jumps / filters rows",
     ];
 
     // 2 border rows + 1 top padding + 1 bottom padding + content lines
@@ -638,7 +1237,7 @@ fn render_help_popup(f: &mut ratatui::Frame<'_>, area: Rect) {
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     let inner = popup_area.inner(Margin { vertical: 2, horizontal: 2 });
@@ -657,7 +1256,7 @@ fn render_create_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppSt
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     // Show input with block cursor (REVERSED so it's visible against paragraph bg)
@@ -675,6 +1274,418 @@ fn render_create_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppSt
     );
 }
 
+fn render_filter_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_area = centered_rect(60, 25, area);
+    let side = if app_state.is_left_active { "left" } else { "right" };
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(format!(" Filter {} panel ", side), STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    f.render_widget(
+        Paragraph::new("Mask, e.g. *.rs;*.toml or !target").alignment(Alignment::Center).style(STYLE_FILE),
+        popup_area.inner(Margin { vertical: 2, horizontal: 2 }),
+    );
+
+    let cursor_style = STYLE_TITLE.add_modifier(Modifier::REVERSED);
+    let input_line = Line::from(app_state.filter_input.cursor_spans(STYLE_TITLE, cursor_style));
+    f.render_widget(
+        Paragraph::new(input_line).alignment(Alignment::Center).style(STYLE_TITLE.bg(COLOR_SELECTED_BACKGROUND)),
+        popup_area.inner(Margin { vertical: 4, horizontal: 2 }),
+    );
+
+    f.render_widget(
+        Paragraph::new("Enter - Apply (empty clears)    Esc - Cancel").alignment(Alignment::Center).style(STYLE_COLUMNS),
+        popup_area.inner(Margin { vertical: 6, horizontal: 2 }),
+    );
+}
+
+fn render_panelize_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_area = centered_rect(60, 25, area);
+    let side = if app_state.is_left_active { "left" } else { "right" };
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(format!(" Panelize into {} panel ", side), STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    f.render_widget(
+        Paragraph::new("Command printing one path per line, or <file to read paths from").alignment(Alignment::Center).style(STYLE_FILE),
+        popup_area.inner(Margin { vertical: 2, horizontal: 2 }),
+    );
+
+    let cursor_style = STYLE_TITLE.add_modifier(Modifier::REVERSED);
+    let input_line = Line::from(app_state.panelize_input.cursor_spans(STYLE_TITLE, cursor_style));
+    f.render_widget(
+        Paragraph::new(input_line).alignment(Alignment::Center).style(STYLE_TITLE.bg(COLOR_SELECTED_BACKGROUND)),
+        popup_area.inner(Margin { vertical: 4, horizontal: 2 }),
+    );
+
+    let instructions = if app_state.panelize_run.is_some() { "Running...    Esc - Stop" } else { "Enter - Run    Esc - Cancel" };
+    f.render_widget(
+        Paragraph::new(instructions).alignment(Alignment::Center).style(STYLE_COLUMNS),
+        Rect::new(popup_area.x + 2, popup_area.y + 6, popup_area.width.saturating_sub(4), 1).intersection(popup_area),
+    );
+}
+
+fn render_compare_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_height = 11.min(area.height);
+    let popup_width = 64.min(area.width);
+    let popup_area = Rect::new(area.x + (area.width - popup_width) / 2, area.y + (area.height - popup_height) / 2, popup_width, popup_height);
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(" Compare Directories ", STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let options = &app_state.compare_options;
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+    let recursive = if options.recursive { "[x]" } else { "[ ]" };
+    let option_lines: Vec<Line> = [format!("Compare by      < {} >", options.method.label()), format!("Subdirectories  {}", recursive)]
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| Line::from(Span::styled(text, if index == app_state.compare_cursor { selected_style } else { STYLE_FILE })))
+        .collect();
+    f.render_widget(Paragraph::new(option_lines), popup_area.inner(Margin { vertical: 2, horizontal: 4 }));
+
+    let status = if let Some(compare) = &app_state.dir_compare {
+        Span::styled(format!("Comparing... {} entries", compare.compared), STYLE_TITLE)
+    } else if !app_state.compare_message.is_empty() {
+        Span::styled(app_state.compare_message.clone(), Style::default().fg(COLOR_SELECTED_MARKER))
+    } else {
+        Span::styled("+ only here   ↑ newer   ! older but larger   ≠ different   ≈ differs inside", STYLE_DIR_DARK)
+    };
+    f.render_widget(Paragraph::new(Line::from(status)).alignment(Alignment::Center), popup_area.inner(Margin { vertical: 5, horizontal: 2 }));
+
+    let instructions = if app_state.dir_compare.is_some() { "Esc - Stop" } else { "Space - Change    Enter - Compare    Esc - Close" };
+    f.render_widget(
+        Paragraph::new(instructions).alignment(Alignment::Center).style(STYLE_COLUMNS),
+        popup_area.inner(Margin { vertical: 7, horizontal: 2 }),
+    );
+}
+
+fn render_sync_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_area = centered_rect(80, 80, area);
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(" Synchronize Directories ", STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = popup_area.inner(Margin { vertical: 1, horizontal: 2 });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+
+    let Some(plan) = &app_state.sync_plan else {
+        let dialog = &app_state.sync_dialog;
+        let roots = format!("{} -> {}", limit_path_string(&app_state.dir_left, 40), limit_path_string(&app_state.dir_right, 40));
+        f.render_widget(Paragraph::new(roots).style(STYLE_FILE), chunks[0]);
+        let label_width = SyncField::ALL.iter().map(|field| field.label().len()).max().unwrap_or(0);
+        let lines: Vec<Line> = SyncField::ALL.iter().enumerate()
+            .map(|(index, &field)| {
+                let is_focused = index == dialog.focus;
+                let label_style = if is_focused { STYLE_TITLE.add_modifier(Modifier::BOLD) } else { STYLE_COLUMNS };
+                Line::from(vec![
+                    Span::styled(format!("{:>width$}: ", field.label(), width = label_width), label_style),
+                    Span::styled(dialog.value(field), if is_focused { selected_style } else { STYLE_FILE }),
+                ])
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[1].inner(Margin { vertical: 1, horizontal: 0 }));
+        f.render_widget(
+            Paragraph::new("Up/Down - Field    Space - Change    Enter - Build plan    Esc - Cancel").alignment(Alignment::Center).style(STYLE_COLUMNS),
+            chunks[2],
+        );
+        return;
+    };
+
+    let status = if let Some(run) = &plan.run {
+        let state = if run.is_cancelled() { "stopping" } else { "syncing" };
+        format!("{} of {} done, {}: {}", run.completed, run.total, state, run.current)
+    } else if !plan.done {
+        format!("{} actions, {} scanned, planning...", plan.actions.len(), plan.scanned)
+    } else if !plan.note.is_empty() {
+        plan.note.clone()
+    } else {
+        format!("{} of {} actions enabled", plan.enabled_count(), plan.actions.len())
+    };
+    f.render_widget(Paragraph::new(status).style(STYLE_COLUMNS), chunks[0]);
+
+    let height = chunks[1].height as usize;
+    let lines: Vec<Line> = if plan.done && plan.actions.is_empty() {
+        vec![Line::from(Span::styled("Nothing to do, the directories are in sync", Style::default().fg(COLOR_SELECTED_MARKER)))]
+    } else {
+        let start = plan.selected.saturating_sub(height / 2).min(plan.actions.len().saturating_sub(height));
+        plan.actions.iter().enumerate().skip(start).take(height)
+            .map(|(index, action)| {
+                let check = if action.enabled { "[x] " } else { "[ ] " };
+                let style = if index == plan.selected { selected_style } else if action.enabled { STYLE_FILE } else { STYLE_DIR_DARK };
+                Line::from(Span::styled(format!("{}{}", check, action.describe()), style))
+            })
+            .collect()
+    };
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+
+    if plan.run.is_some() {
+        f.render_widget(Paragraph::new("Esc - Stop after the current action").alignment(Alignment::Center).style(STYLE_COLUMNS), chunks[2]);
+    } else if let Some(input) = &app_state.sync_export {
+        let cursor_style = STYLE_TITLE.add_modifier(Modifier::REVERSED);
+        let mut spans = vec![Span::styled("Save plan to: ", STYLE_COLUMNS)];
+        spans.extend(input.cursor_spans(STYLE_TITLE, cursor_style));
+        f.render_widget(Paragraph::new(Line::from(spans)).style(STYLE_TITLE.bg(COLOR_SELECTED_BACKGROUND)), chunks[2]);
+    } else {
+        f.render_widget(
+            Paragraph::new("Space - Toggle    * - Invert    Enter - Run    Ctrl+S - Save plan    Esc - Back").alignment(Alignment::Center).style(STYLE_COLUMNS),
+            chunks[2],
+        );
+    }
+}
+
+fn render_mounts_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_area = centered_rect(80, 70, area);
+    let title = if app_state.mounts_for_left { " Mounts - Left Panel " } else { " Mounts - Right Panel " };
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(title, STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = popup_area.inner(Margin { vertical: 1, horizontal: 2 });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+
+    // Mount point takes whatever the fixed columns leave
+    let path_width = (inner.width as usize).saturating_sub(1 + 10 + 1 + 16 + 1 + 10 + 1 + 10 + 1 + 5).max(12);
+    let header = format!("{:<path_width$} {:<10} {:<16} {:>10} {:>10} {:>5}", "Mount point", "Type", "Device", "Size", "Free", "Use%");
+    f.render_widget(Paragraph::new(header).style(STYLE_COLUMNS), chunks[0]);
+
+    let height = chunks[1].height as usize;
+    let start = app_state.mounts_selected.saturating_sub(height / 2).min(app_state.mounts.len().saturating_sub(height));
+    let lines: Vec<Line> = app_state.mounts.iter().enumerate().skip(start).take(height)
+        .map(|(index, mount)| {
+            // "?" until statvfs answers for the mount
+            let used_percent = mount.space.map(|space| space.total.saturating_sub(space.free).saturating_mul(100) / space.total.max(1));
+            let total = mount.space.map_or("?".to_string(), |space| format_size(space.total));
+            let free = mount.space.map_or("?".to_string(), |space| format_size(space.free));
+            let percent = used_percent.map_or("?".to_string(), |percent| format!("{}%", percent));
+            let source: String = mount.source.chars().take(16).collect();
+            let text = format!(
+                "{:<path_width$} {:<10} {:<16} {:>10} {:>10} {:>5}",
+                limit_path_string(&mount.mount_point, path_width.saturating_sub(3)),
+                mount.fs_type.chars().take(10).collect::<String>(),
+                source,
+                total,
+                free,
+                percent
+            );
+            let style = if index == app_state.mounts_selected { selected_style } else if used_percent.is_some_and(|percent| percent >= 90) { Style::new().fg(COLOR_SELECTED_MARKER) } else { STYLE_FILE };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+
+    f.render_widget(
+        Paragraph::new("Enter - Go to mount point    Esc - Close").alignment(Alignment::Center).style(STYLE_COLUMNS),
+        chunks[2],
+    );
+}
+
+fn render_find_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let popup_area = centered_rect(80, 80, area);
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(" Find File ", STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = popup_area.inner(Margin { vertical: 1, horizontal: 2 });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if let Some(search) = &app_state.find_search {
+        let state = if search.done { "done" } else { "searching..." };
+        let status = format!("{} found, {} scanned, {}", search.results.len(), search.scanned, state);
+        f.render_widget(Paragraph::new(status).style(STYLE_COLUMNS), chunks[0]);
+
+        let height = chunks[1].height as usize;
+        let start = search.selected.saturating_sub(height / 2).min(search.results.len().saturating_sub(height));
+        let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+        let lines: Vec<Line> = search.results.iter().enumerate().skip(start).take(height)
+            .map(|(index, result)| {
+                let relative = result.path.strip_prefix(&search.start_dir).unwrap_or(&result.path);
+                let is_selected = index == search.selected;
+                let style = if is_selected { selected_style } else { STYLE_FILE };
+                let mut spans = vec![Span::styled(display_name(relative.as_os_str()), style)];
+                if let Some(member) = &result.archive_entry {
+                    spans.push(Span::styled(format!("!/{}", member), style));
+                }
+                if let Some(line) = result.line {
+                    // file:line: snippet, with the matched text emphasized
+                    let (before, matched, after) = split_at_range(&result.snippet, &result.snippet_range);
+                    let indent = before.len() - before.trim_start().len();
+                    spans.push(Span::styled(format!(":{}: ", line + 1), if is_selected { style } else { STYLE_COLUMNS }));
+                    spans.push(Span::styled(before[indent..].replace('\t', TAB_SPACES), style));
+                    spans.push(Span::styled(matched.to_string(), style.fg(COLOR_SELECTED_MARKER).add_modifier(Modifier::BOLD)));
+                    spans.push(Span::styled(after.replace('\t', TAB_SPACES), style));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[1]);
+
+        f.render_widget(
+            Paragraph::new("Enter - Go to file    F3 - View    F4 - Edit    Alt+P - Panelize    Esc - Back to search").alignment(Alignment::Center).style(STYLE_COLUMNS),
+            chunks[2],
+        );
+    } else {
+        let dialog = &app_state.find_dialog;
+        let label_width = FindField::ALL.iter().map(|field| field.label().len()).max().unwrap_or(0);
+        let cursor_style = STYLE_TITLE.add_modifier(Modifier::REVERSED);
+        let lines: Vec<Line> = FindField::ALL.iter().enumerate()
+            .map(|(index, &field)| {
+                let is_focused = index == dialog.focus;
+                let label_style = if is_focused { STYLE_TITLE.add_modifier(Modifier::BOLD) } else { STYLE_COLUMNS };
+                let mut spans = vec![Span::styled(format!("{:>width$}: ", field.label(), width = label_width), label_style)];
+                match dialog.input(field) {
+                    Some(input) if is_focused => spans.extend(input.cursor_spans(STYLE_TITLE, cursor_style)),
+                    Some(input) => spans.push(Span::styled(input.text.clone(), STYLE_FILE)),
+                    None => spans.push(Span::styled(dialog.toggle_value(field), if is_focused { cursor_style } else { STYLE_FILE })),
+                }
+                Line::from(spans)
+            })
+            .collect();
+        f.render_widget(Paragraph::new("Names: globs (*.rs;*.toml) or word, sizes like 10K/5M, depth 0 = start directory only, empty = any").style(STYLE_FILE), chunks[0]);
+        f.render_widget(Paragraph::new(lines), chunks[1].inner(Margin { vertical: 1, horizontal: 0 }));
+
+        f.render_widget(
+            Paragraph::new("Tab/Up/Down - Field    Space - Toggle    Enter - Search    Esc - Cancel").alignment(Alignment::Center).style(STYLE_COLUMNS),
+            chunks[2],
+        );
+    }
+}
+
+fn render_fuzzy_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let Some(finder) = &app_state.fuzzy_finder else { return };
+    let popup_area = centered_rect(70, 70, area);
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(format!(" Go to File: {} ", limit_path_string(&finder.root, 40)), STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = popup_area.inner(Margin { vertical: 1, horizontal: 2 });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let cursor_style = STYLE_TITLE.add_modifier(Modifier::REVERSED);
+    let mut query_spans = vec![Span::styled("> ", STYLE_COLUMNS)];
+    query_spans.extend(finder.query.cursor_spans(STYLE_TITLE, cursor_style));
+    f.render_widget(Paragraph::new(Line::from(query_spans)).style(STYLE_TITLE.bg(COLOR_SELECTED_BACKGROUND)), chunks[0]);
+
+    let state = if finder.done { "" } else { ", indexing..." };
+    let shown = if finder.match_count > finder.matches.len() { format!(" (best {} shown)", finder.matches.len()) } else { String::new() };
+    let status = format!("{} of {} files{}{}", finder.match_count, finder.indexed, shown, state);
+    f.render_widget(Paragraph::new(status).style(STYLE_COLUMNS), chunks[1]);
+
+    let height = chunks[2].height as usize;
+    let start = finder.selected.saturating_sub(height / 2).min(finder.matches.len().saturating_sub(height));
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = finder.matches.iter().enumerate().skip(start).take(height)
+        .map(|(index, found)| {
+            let style = if index == finder.selected { selected_style } else { STYLE_FILE };
+            Line::from(highlight_chars(&found.display, &found.positions, style, style.fg(COLOR_SELECTED_MARKER).add_modifier(Modifier::BOLD)))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[2]);
+
+    f.render_widget(
+        Paragraph::new("Enter - Go to file    F3 - View    F4 - Edit    Esc - Close").alignment(Alignment::Center).style(STYLE_COLUMNS),
+        chunks[3],
+    );
+}
+
+/// Splits `text` into spans, styling the chars at `positions` (sorted char indices) with `matched_style`.
+fn highlight_chars(text: &str, positions: &[usize], style: Style, matched_style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    let mut next = positions.iter().peekable();
+    for (index, c) in text.chars().enumerate() {
+        let is_matched = next.next_if(|&&pos| pos == index).is_some();
+        if is_matched != run_matched && !run.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut run), if run_matched { matched_style } else { style }));
+        }
+        run_matched = is_matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { matched_style } else { style }));
+    }
+    spans
+}
+
+fn render_columns_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
+    let view = if app_state.is_left_active { &app_state.view_left } else { &app_state.view_right };
+    let side = if app_state.is_left_active { "left" } else { "right" };
+    let popup_height = (view.columns.len() as u16 + 6).min(area.height);
+    let popup_width = 60.min(area.width);
+    let popup_area = Rect::new(area.x + (area.width - popup_width) / 2, area.y + (area.height - popup_height) / 2, popup_width, popup_height);
+    let popup_block = Block::default()
+        .title(Line::from(Span::styled(format!(" Columns - {} panel ", side), STYLE_TITLE)).centered())
+        .borders(Borders::ALL)
+        .style(STYLE_BORDER);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = popup_area.inner(Margin { vertical: 1, horizontal: 2 });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .split(inner);
+
+    let selected_style = Style::default().bg(COLOR_SELECTED_BACKGROUND).fg(COLOR_SELECTED_FOREGROUND).add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = view.columns.iter().enumerate()
+        .map(|(index, setting)| {
+            let check = if setting.enabled { "[x]" } else { "[ ]" };
+            let text = format!("{} {:<14} width {:>2}", check, setting.column.title(), setting.width);
+            let style = if index == app_state.columns_cursor { selected_style } else if setting.enabled { STYLE_FILE } else { STYLE_DIR_DARK };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[0]);
+
+    f.render_widget(
+        Paragraph::new("Space - Show/hide    Shift+Up/Down - Move\nLeft/Right - Width    Esc - Close").alignment(Alignment::Center).style(STYLE_COLUMNS),
+        chunks[1],
+    );
+}
+
 fn render_delete_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppState) {
     let count = app_state.delete_items.len();
     let popup_area = centered_rect(60, 30, area);
@@ -691,14 +1702,14 @@ fn render_delete_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &AppSt
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     // Message
     let message = if count == 1 {
-        format!("Delete \"{}\"?", app_state.delete_items[0].0)
+        format!("Delete \"{}\"?", file_name_lossy(&app_state.delete_items[0].0))
     } else {
-        let names: Vec<&str> = app_state.delete_items.iter().map(|(name, _)| name.as_str()).collect();
+        let names: Vec<String> = app_state.delete_items.iter().map(|(path, _)| file_name_lossy(path)).collect();
         format!("Delete {} items?\n\n{}", count, names.join(", "))
     };
     f.render_widget(Paragraph::new(message).alignment(Alignment::Center).style(STYLE_TITLE), popup_area.inner(Margin { vertical: 2, horizontal: 2 }));
@@ -732,19 +1743,15 @@ fn render_copy_move_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &Ap
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     // Source info
     let source_msg = if count == 1 {
-        let source_name = items[0].0.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Unknown");
+        let source_name = file_name_lossy(&items[0].0);
         format!("{} \"{}\"", verb, source_name)
     } else {
-        let names: Vec<&str> = items.iter()
-            .filter_map(|(src, _, _)| src.file_name().and_then(|n| n.to_str()))
-            .collect();
+        let names: Vec<String> = items.iter().map(|(src, _, _)| file_name_lossy(src)).collect();
         format!("{} {} items: {}", verb, count, names.join(", "))
     };
     f.render_widget(
@@ -752,14 +1759,22 @@ fn render_copy_move_popup(f: &mut ratatui::Frame<'_>, area: Rect, app_state: &Ap
         popup_area.inner(Margin { vertical: 2, horizontal: 2 }),
     );
 
-    // Destination directory
-    let dest_dir = items[0].1.parent().map(|p| p.to_path_buf()).unwrap_or_default();
-    let dest_display = limit_path_string(&dest_dir, popup_area.width as usize - 10);
+    // Destination directory; entries from inside compared directories land below it
+    let dest_dir = if app_state.is_left_active { &app_state.dir_right } else { &app_state.dir_left };
+    let dest_display = limit_path_string(dest_dir, popup_area.width as usize - 10);
     f.render_widget(
         Paragraph::new(format!("to: {}", dest_display)).alignment(Alignment::Center).style(STYLE_FILE),
         popup_area.inner(Margin { vertical: 4, horizontal: 2 }),
     );
 
+    if is_copy && app_state.copy_overwrites > 0 {
+        let warning = if count == 1 { "Destination exists and will be overwritten".to_string() } else { format!("{} existing will be overwritten", app_state.copy_overwrites) };
+        f.render_widget(
+            Paragraph::new(warning).alignment(Alignment::Center).style(Style::new().fg(COLOR_SELECTED_MARKER)),
+            Rect::new(popup_area.x + 2, popup_area.y + 5, popup_area.width.saturating_sub(4), 1).intersection(popup_area),
+        );
+    }
+
     // Instructions
     f.render_widget(
         Paragraph::new("Y / Enter - Yes    N / Esc - No").alignment(Alignment::Center).style(STYLE_COLUMNS),
@@ -774,7 +1789,7 @@ fn render_editor_save_popup(f: &mut ratatui::Frame<'_>, area: Rect) {
         .borders(Borders::ALL)
         .style(STYLE_BORDER);
 
-    f.render_widget(Clear::default(), popup_area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(popup_block, popup_area);
 
     f.render_widget(
@@ -788,6 +1803,14 @@ fn render_editor_save_popup(f: &mut ratatui::Frame<'_>, area: Rect) {
     );
 }
 
+/// Splits `text` into (before, matched, after) around a byte range, tolerating bad ranges.
+fn split_at_range<'a>(text: &'a str, range: &std::ops::Range<usize>) -> (&'a str, &'a str, &'a str) {
+    match (text.get(..range.start), text.get(range.clone()), text.get(range.end..)) {
+        (Some(before), Some(matched), Some(after)) => (before, matched, after),
+        _ => (text, "", ""),
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1,5 +1,6 @@
 use crate::constants::*;
 use ratatui::style::Color;
+use std::ffi::{OsStr, OsString};
 use std::path::Path;
 
 // Converts bytes to human-readable format with binary prefixes (KiB, MiB, etc.)
@@ -15,6 +16,40 @@ pub fn format_size(bytes: u64) -> String {
     format!("{:.0} {}", size, UNITS[unit_index])
 }
 
+/// Parses sizes like "512", "10K", "1.5M" or "2 GiB" (binary multiples) into bytes.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let exponent = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+/// Classic hex dump lines: offset, 16 bytes in two groups of 8, printable ASCII.
+pub fn hex_dump(bytes: &[u8], start_offset: u64) -> Vec<String> {
+    bytes.chunks(16).enumerate()
+        .map(|(row, chunk)| {
+            let mut hex = String::with_capacity(50);
+            for (i, byte) in chunk.iter().enumerate() {
+                hex.push_str(&format!("{:02x} ", byte));
+                if i == 7 {
+                    hex.push(' ');
+                }
+            }
+            let ascii: String = chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect();
+            format!("{:08x}  {:<49} |{}|", start_offset + row as u64 * 16, hex, ascii)
+        })
+        .collect()
+}
+
 pub fn color_for_extension(ext: &str) -> Color {
     if ext.is_empty() {
         return COLOR_FILE;
@@ -44,7 +79,190 @@ pub fn color_for_extension(ext: &str) -> Color {
     )
 }
 
+/// Last path component for display, empty for roots.
+pub fn file_name_lossy(path: &Path) -> String {
+    path.file_name().map(display_name).unwrap_or_default()
+}
+
+/// Display form of a name or path: bytes that are not valid UTF-8 show as `\xNN` escapes (and
+/// backslashes of such names as `\\`), so distinct names stay distinct on screen. Filesystem
+/// operations use the original `OsStr`.
+#[cfg(unix)]
+pub fn display_name(name: &OsStr) -> String {
+    use std::fmt::Write;
+    use std::os::unix::ffi::OsStrExt;
+
+    if let Some(text) = name.to_str() {
+        return text.to_string();
+    }
+    let mut text = String::with_capacity(name.len() + 8);
+    for chunk in name.as_bytes().utf8_chunks() {
+        text.push_str(&chunk.valid().replace('\\', "\\\\"));
+        for byte in chunk.invalid() {
+            let _ = write!(text, "\\x{:02X}", byte);
+        }
+    }
+    text
+}
+
+#[cfg(not(unix))]
+pub fn display_name(name: &OsStr) -> String {
+    name.to_string_lossy().into_owned()
+}
+
+/// Turns an edited `display_name` back into a file name: when the original name was not
+/// valid UTF-8, `\xNN` and `\\` escapes become raw bytes again; otherwise `text` is taken literally.
+#[cfg(unix)]
+pub fn name_from_display(text: &str, original: &OsStr) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    if original.to_str().is_some() {
+        return OsString::from(text);
+    }
+    let bytes = text.as_bytes();
+    let mut name = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes.get(index..index + 2) {
+            Some(b"\\\\") => Some((b'\\', 2)),
+            Some(b"\\x") => bytes.get(index + 2..index + 4).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()).map(|byte| (byte, 4)),
+            _ => None,
+        };
+        match escaped {
+            Some((byte, len)) => {
+                name.push(byte);
+                index += len;
+            }
+            None => {
+                name.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    OsString::from_vec(name)
+}
+
+#[cfg(not(unix))]
+pub fn name_from_display(text: &str, _original: &OsStr) -> OsString {
+    OsString::from(text)
+}
+
 pub fn limit_path_string(path: &Path, n: usize) -> String {
-    let path_string = path.display().to_string();
-    if path_string.len() <= n { path_string } else { format!("...{}", &path_string[(path_string.len() - n)..]) }
+    let path_string = display_name(path.as_os_str());
+    let len = path_string.chars().count();
+    if len <= n { path_string } else { format!("...{}", path_string.chars().skip(len - n).collect::<String>()) }
+}
+
+/// Case-insensitive wildcard match supporting `*` (any run) and `?` (any single char).
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    wildcard_positions(pattern, text).is_some()
+}
+
+/// Like `wildcard_match`, returning the positions of the chars in `text` matched by literals and `?`.
+pub fn wildcard_positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let pattern: Vec<char> = pattern.chars().map(fold_case).collect();
+    let text: Vec<char> = text.chars().map(fold_case).collect();
+    let (mut p, mut t) = (0, 0);
+    let mut positions = Vec::new();
+    // Last '*' seen, with the text position it resumes from and the positions recorded before it
+    let mut backtrack: Option<(usize, usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            positions.push(t);
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t, positions.len()));
+            p += 1;
+        } else if let Some((star_p, star_t, recorded)) = backtrack {
+            // Let the last '*' swallow one more char and retry
+            positions.truncate(recorded);
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1, recorded));
+        } else {
+            return None;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*').then_some(positions)
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Greedy subsequence match of `pattern` in `text[from..]`, tightened from the right.
+/// Returns matched char positions.
+fn fuzzy_positions(pattern: &[char], text: &[char], from: usize) -> Option<Vec<usize>> {
+    // Forward pass finds where the first full match ends
+    let mut p = 0;
+    let mut end = from;
+    while p < pattern.len() {
+        let t = text.get(end..)?.iter().position(|&c| fold_case(c) == pattern[p])?;
+        end += t + 1;
+        p += 1;
+    }
+
+    // Backward pass from that end picks the shortest window
+    let mut positions = vec![0; pattern.len()];
+    let mut t = end;
+    for (slot, &wanted) in positions.iter_mut().zip(pattern).rev() {
+        t -= 1;
+        while fold_case(text[t]) != wanted {
+            t -= 1;
+        }
+        *slot = t;
+    }
+    Some(positions)
+}
+
+fn fuzzy_score(text: &[char], positions: &[usize], basename_start: usize) -> i32 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    for &pos in positions {
+        score += 16;
+        let before = pos.checked_sub(1).map(|i| text[i]);
+        match before {
+            None => score += 12,
+            Some('/' | '\\' | '_' | '-' | '.' | ' ') => score += 10,
+            Some(b) if b.is_lowercase() && text[pos].is_uppercase() => score += 8,
+            _ => {}
+        }
+        if pos == basename_start {
+            score += 8;
+        }
+        if pos >= basename_start {
+            score += 4;
+        }
+        if let Some(prev) = previous {
+            let gap = pos - prev - 1;
+            score += if gap == 0 { 12 } else { -(gap.min(12) as i32) - 2 };
+        }
+        previous = Some(pos);
+    }
+    // Shorter candidates win ties
+    score - (text.len() / 8) as i32
+}
+
+/// Case-insensitive fuzzy match. Returns a score (higher is better) and the matched char indices.
+/// Matches inside the last path component are preferred.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).map(fold_case).collect();
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let text: Vec<char> = text.chars().collect();
+    let basename_start = text.iter().rposition(|&c| c == '/' || c == '\\').map_or(0, |i| i + 1);
+
+    let whole = fuzzy_positions(&pattern, &text, 0)?;
+    let mut best = (fuzzy_score(&text, &whole, basename_start), whole);
+    if basename_start > 0 && let Some(in_name) = fuzzy_positions(&pattern, &text, basename_start) {
+        let score = fuzzy_score(&text, &in_name, basename_start);
+        if score > best.0 {
+            best = (score, in_name);
+        }
+    }
+    Some(best)
 }
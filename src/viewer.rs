@@ -1,7 +1,11 @@
+use crate::fs_ops::open_regular_file;
+use crate::lineindex::LineIndex;
 use ratatui::style::Color;
 use ratatui::text::Span;
-use std::fs::File;
-use std::io::{Error, Read};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use syntect::easy::HighlightLines;
@@ -11,75 +15,364 @@ use syntect::parsing::SyntaxSet;
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
 static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
 
+/// Bytes per hex dump row.
+pub const HEX_ROW_BYTES: u64 = 16;
+/// Text mode reads this many lines around the ones on screen.
+const TEXT_WINDOW_LINES: usize = 256;
+/// Hex mode reads the file in pages of this size around the rows on screen.
+const HEX_PAGE_SIZE: u64 = 64 * 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ViewerMode {
+    Text,
+    Hex,
+}
+
+/// Overwrites made in the hex editor (F4 on a binary file, or F2 in the viewer), kept in memory until saved.
+#[derive(Default)]
+pub struct HexEdit {
+    /// Offset of the byte under the cursor.
+    pub cursor: u64,
+    /// Typing goes to the ASCII column instead of the hex digits.
+    pub is_ascii: bool,
+    /// The next hex digit replaces the low half of the byte.
+    pub is_low_nibble: bool,
+    /// New values by offset; only these bytes are written back.
+    pub changes: BTreeMap<u64, u8>,
+    /// Offset and earlier change of every edit, newest last.
+    undo: Vec<(u64, Option<u8>)>,
+}
+
 pub struct ViewerState {
     pub file_path: PathBuf,
-    pub content_lines: Vec<String>,
+    pub mode: ViewerMode,
+    /// Scroll position of the mode not shown, restored when switching back.
+    other_scroll_offset: usize,
+    file: File,
+    /// Built in the background once text mode is first shown.
+    index: Option<LineIndex>,
+    /// Lines from `window_start` on, the part of the file text mode last read.
+    window: Vec<String>,
+    window_start: usize,
+    /// The window reaches the end of the file.
+    is_window_at_end: bool,
+    /// Bytes from `page_offset` on, the part of the file hex mode last read.
+    page: Vec<u8>,
+    page_offset: u64,
     pub scroll_offset: usize,
     pub horizontal_offset: usize,
+    /// Lines indexed so far, every line once indexing is done.
     pub total_lines: usize,
     pub file_size: u64,
     pub is_binary: bool,
     pub syntax_name: String,
     pub from_edit: bool,
+    /// Line and byte range to emphasize, e.g. a content search match.
+    pub highlight: Option<(usize, Range<usize>)>,
+    /// Set while the hex view is editing the file.
+    pub hex_edit: Option<HexEdit>,
 }
 
+/// Fails for anything but a regular file, see `open_regular_file`.
 pub fn is_binary_file(path: &Path) -> Result<bool, Error> {
-    let mut file = File::open(path)?;
+    let mut file = open_regular_file(path)?;
     let mut buffer = [0; 512];
     let bytes_read = file.read(&mut buffer)?;
+    Ok(is_binary_content(&buffer[..bytes_read]))
+}
+
+/// Judges content by its first bytes (up to 512 are looked at), e.g. of an archive member.
+pub fn is_binary_content(head: &[u8]) -> bool {
+    let head = &head[..head.len().min(512)];
 
     // Check for null bytes
-    if buffer[..bytes_read].contains(&0) {
-        return Ok(true);
+    if head.contains(&0) {
+        return true;
     }
 
     // Check UTF-8 validity (allow incomplete sequence at buffer boundary)
-    match std::str::from_utf8(&buffer[..bytes_read]) {
-        Ok(_) => Ok(false),
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
         // error_len() is None for truncated multi-byte sequence at end of buffer
-        Err(e) => Ok(e.error_len().is_some()),
+        Err(e) => e.error_len().is_some(),
     }
 }
 
+/// Opens text files as lines and binary files as a hex dump, both read from disk only as far as shown.
 pub fn load_file_content(path: &Path) -> Result<ViewerState, Error> {
+    let file = open_regular_file(path)?;
     // Get metadata once (single stat syscall)
-    let file_size = std::fs::metadata(path)?.len();
-
-    // Check binary first
-    if is_binary_file(path)? {
-        return Ok(ViewerState {
-            file_path: path.to_path_buf(),
-            content_lines: vec!["Binary file detected.".to_string()],
-            scroll_offset: 0,
-            horizontal_offset: 0,
-            total_lines: 1,
-            file_size,
-            is_binary: true,
-            syntax_name: "Binary".to_string(),
-            from_edit: false,
-        });
-    }
-
-    // Load text file
-    let content = std::fs::read_to_string(path)?;
-    let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-    if content.ends_with('\n') {
-        lines.push(String::new());
-    }
-    let total_lines = lines.len().max(1); // At least 1 line for empty files
-    let syntax_name = detect_syntax(path);
-
-    Ok(ViewerState {
+    let file_size = file.metadata()?.len();
+    let is_binary = is_binary_file(path)?;
+
+    let mut state = ViewerState {
         file_path: path.to_path_buf(),
-        content_lines: lines,
+        mode: if is_binary { ViewerMode::Hex } else { ViewerMode::Text },
+        other_scroll_offset: 0,
+        file,
+        index: None,
+        window: Vec::new(),
+        window_start: 0,
+        is_window_at_end: false,
+        page: Vec::new(),
+        page_offset: 0,
         scroll_offset: 0,
         horizontal_offset: 0,
-        total_lines,
+        total_lines: 1,
         file_size,
-        is_binary: false,
+        is_binary,
+        syntax_name: if is_binary { "Binary".to_string() } else { detect_syntax(path) },
         from_edit: false,
-        syntax_name,
-    })
+        highlight: None,
+        hex_edit: None,
+    };
+    if !is_binary {
+        state.index = Some(LineIndex::start(path.to_path_buf()));
+    }
+    Ok(state)
+}
+
+impl ViewerState {
+    /// Rows of the current mode: text lines or hex dump rows.
+    pub fn line_count(&self) -> usize {
+        match self.mode {
+            ViewerMode::Text => self.total_lines,
+            ViewerMode::Hex => self.file_size.div_ceil(HEX_ROW_BYTES).max(1) as usize,
+        }
+    }
+
+    /// Switches between text and hex, keeping each mode's scroll position.
+    pub fn toggle_mode(&mut self) {
+        if self.index.is_none() {
+            self.index = Some(LineIndex::start(self.file_path.clone()));
+        }
+        self.mode = match self.mode {
+            ViewerMode::Text => ViewerMode::Hex,
+            ViewerMode::Hex => ViewerMode::Text,
+        };
+        std::mem::swap(&mut self.scroll_offset, &mut self.other_scroll_offset);
+        self.horizontal_offset = 0;
+    }
+
+    /// Takes in indexing progress; the error if reading the file failed.
+    pub fn poll(&mut self) -> Option<String> {
+        let index = self.index.as_mut()?;
+        if index.done {
+            return None;
+        }
+        let error = index.poll();
+        self.total_lines = index.lines();
+        error
+    }
+
+    /// Estimated line count while the file is still being indexed.
+    pub fn approx_lines(&self) -> Option<usize> {
+        self.index.as_ref().and_then(|index| index.estimate(self.file_size))
+    }
+
+    /// Up to `count` text lines from `start` on, read from disk when they are outside the cached window.
+    /// Lines are decoded lossily, so binary content shows replacement characters.
+    pub fn text_lines(&mut self, start: usize, count: usize) -> Result<&[String], Error> {
+        let Some(index) = &self.index else { return Ok(&[]) };
+        let window_end = self.window_start + self.window.len();
+        if start < self.window_start || (start + count > window_end && !self.is_window_at_end) || self.window.is_empty() {
+            // Read around the visible lines so scrolling either way stays in the window
+            let first = start.saturating_sub(TEXT_WINDOW_LINES / 2);
+            let wanted = start - first + count + TEXT_WINDOW_LINES / 2;
+            self.window = index.read_lines(&mut self.file, first, wanted)?;
+            self.window_start = first;
+            self.is_window_at_end = self.window.len() < wanted;
+        }
+        let from = (start - self.window_start).min(self.window.len());
+        let to = (from + count).min(self.window.len());
+        Ok(&self.window[from..to])
+    }
+
+    /// Bytes of `rows` hex rows from `first_row` on, read from disk when they are outside the cached page.
+    pub fn hex_bytes(&mut self, first_row: usize, rows: usize) -> Result<&[u8], Error> {
+        let start = (first_row as u64 * HEX_ROW_BYTES).min(self.file_size);
+        let end = (start + rows as u64 * HEX_ROW_BYTES).min(self.file_size);
+        let page_end = self.page_offset + self.page.len() as u64;
+        if start < self.page_offset || end > page_end {
+            let page_start = start - start % HEX_PAGE_SIZE;
+            let length = HEX_PAGE_SIZE.max(end - page_start);
+            self.page.clear();
+            self.file.seek(SeekFrom::Start(page_start))?;
+            (&mut self.file).take(length).read_to_end(&mut self.page)?;
+            self.page_offset = page_start;
+        }
+        let from = (start - self.page_offset) as usize;
+        let to = ((end - self.page_offset) as usize).min(self.page.len());
+        Ok(&self.page[from.min(to)..to])
+    }
+
+    /// Scrolls to a byte offset in hex mode (`0x1f0`, `1f0h` or decimal) or a line number in text mode.
+    pub fn go_to(&mut self, target: &str, viewport: usize) -> Result<(), String> {
+        let target = target.trim();
+        let row = match self.mode {
+            ViewerMode::Hex => {
+                let offset = parse_offset(target).ok_or_else(|| format!("Invalid offset: {}", target))?;
+                if offset >= self.file_size.max(1) {
+                    return Err(format!("Offset {:#x} is past the end of the file ({:#x} bytes)", offset, self.file_size));
+                }
+                if let Some(edit) = &mut self.hex_edit {
+                    edit.cursor = offset;
+                    edit.is_low_nibble = false;
+                }
+                (offset / HEX_ROW_BYTES) as usize
+            }
+            ViewerMode::Text => {
+                let line: usize = target.parse().ok().filter(|line| *line > 0).ok_or_else(|| format!("Invalid line number: {}", target))?;
+                line.min(self.total_lines) - 1
+            }
+        };
+        self.scroll_offset = row.min(self.line_count().saturating_sub(viewport));
+        Ok(())
+    }
+}
+
+impl ViewerState {
+    /// Switches to hex mode with the cursor on the first byte shown.
+    pub fn start_hex_edit(&mut self) {
+        if self.mode == ViewerMode::Text {
+            self.toggle_mode();
+        }
+        let cursor = (self.scroll_offset as u64 * HEX_ROW_BYTES).min(self.file_size.saturating_sub(1));
+        self.hex_edit = Some(HexEdit { cursor, ..HexEdit::default() });
+    }
+
+    pub fn is_hex_modified(&self) -> bool {
+        self.hex_edit.as_ref().is_some_and(|edit| !edit.changes.is_empty())
+    }
+
+    /// Moves the cursor by `delta` bytes, stopping at either end, and scrolls it into view.
+    pub fn hex_move(&mut self, delta: i64, viewport: usize) {
+        let last = self.file_size.saturating_sub(1);
+        if let Some(edit) = &mut self.hex_edit {
+            edit.cursor = edit.cursor.saturating_add_signed(delta).min(last);
+            edit.is_low_nibble = false;
+        }
+        self.reveal_cursor(viewport);
+    }
+
+    /// Moves the cursor to the first (`to_end` false) or last byte of its row.
+    pub fn hex_row_edge(&mut self, to_end: bool, viewport: usize) {
+        let Some(edit) = &self.hex_edit else { return };
+        let column = (edit.cursor % HEX_ROW_BYTES) as i64;
+        let delta = if to_end { HEX_ROW_BYTES as i64 - 1 - column } else { -column };
+        self.hex_move(delta, viewport);
+    }
+
+    pub fn hex_toggle_column(&mut self) {
+        if let Some(edit) = &mut self.hex_edit {
+            edit.is_ascii = !edit.is_ascii;
+            edit.is_low_nibble = false;
+        }
+    }
+
+    /// A hex digit in the hex column or a printable ASCII character in the ASCII column overwrites the cursor byte.
+    pub fn hex_type(&mut self, c: char, viewport: usize) -> Result<(), Error> {
+        let Some(edit) = &self.hex_edit else { return Ok(()) };
+        if self.file_size == 0 {
+            return Ok(());
+        }
+        let (offset, is_ascii, is_low_nibble) = (edit.cursor, edit.is_ascii, edit.is_low_nibble);
+        let value = if is_ascii {
+            if !(c.is_ascii_graphic() || c == ' ') {
+                return Ok(());
+            }
+            c as u8
+        } else {
+            let Some(digit) = c.to_digit(16) else { return Ok(()) };
+            let current = self.byte_at(offset)?;
+            if is_low_nibble { (current & 0xf0) | digit as u8 } else { (current & 0x0f) | (digit as u8) << 4 }
+        };
+        self.set_byte(offset, value)?;
+        if !is_ascii && !is_low_nibble {
+            if let Some(edit) = &mut self.hex_edit {
+                edit.is_low_nibble = true;
+            }
+        } else {
+            self.hex_move(1, viewport);
+        }
+        Ok(())
+    }
+
+    /// Reverts the latest edit and puts the cursor back on its byte.
+    pub fn hex_undo(&mut self, viewport: usize) {
+        let Some(edit) = &mut self.hex_edit else { return };
+        let Some((offset, earlier)) = edit.undo.pop() else { return };
+        match earlier {
+            Some(value) => edit.changes.insert(offset, value),
+            None => edit.changes.remove(&offset),
+        };
+        edit.cursor = offset;
+        edit.is_low_nibble = false;
+        self.reveal_cursor(viewport);
+    }
+
+    /// Writes the changed bytes in place; the file keeps its length.
+    pub fn save_hex(&mut self) -> Result<(), Error> {
+        let Some(edit) = &mut self.hex_edit else { return Ok(()) };
+        let mut file = OpenOptions::new().write(true).open(&self.file_path)?;
+        for (&offset, &value) in &edit.changes {
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&[value])?;
+        }
+        edit.changes.clear();
+        edit.undo.clear();
+        // Re-read what is now on disk
+        self.page.clear();
+        self.window.clear();
+        Ok(())
+    }
+
+    /// Current value of the byte at `offset`, edits included.
+    fn byte_at(&mut self, offset: u64) -> Result<u8, Error> {
+        match self.hex_edit.as_ref().and_then(|edit| edit.changes.get(&offset)) {
+            Some(&value) => Ok(value),
+            None => self.original_byte(offset),
+        }
+    }
+
+    fn original_byte(&mut self, offset: u64) -> Result<u8, Error> {
+        let row = (offset / HEX_ROW_BYTES) as usize;
+        let bytes = self.hex_bytes(row, 1)?;
+        bytes.get((offset % HEX_ROW_BYTES) as usize).copied().ok_or_else(|| Error::other("Offset past the end of the file"))
+    }
+
+    /// Records the edit for undo; writing back the original value drops the change.
+    fn set_byte(&mut self, offset: u64, value: u8) -> Result<(), Error> {
+        let original = self.original_byte(offset)?;
+        let Some(edit) = &mut self.hex_edit else { return Ok(()) };
+        edit.undo.push((offset, edit.changes.get(&offset).copied()));
+        if value == original {
+            edit.changes.remove(&offset);
+        } else {
+            edit.changes.insert(offset, value);
+        }
+        Ok(())
+    }
+
+    fn reveal_cursor(&mut self, viewport: usize) {
+        let Some(edit) = &self.hex_edit else { return };
+        let row = (edit.cursor / HEX_ROW_BYTES) as usize;
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+        } else if row >= self.scroll_offset + viewport.max(1) {
+            self.scroll_offset = row + 1 - viewport.max(1);
+        }
+    }
+}
+
+/// `0x` prefix or `h` suffix for hex, decimal otherwise.
+fn parse_offset(text: &str) -> Option<u64> {
+    let lower = text.to_ascii_lowercase();
+    if let Some(hex) = lower.strip_prefix("0x").or_else(|| lower.strip_suffix('h')) {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        lower.parse().ok()
+    }
 }
 
 pub fn detect_syntax(path: &Path) -> String {
@@ -102,8 +395,8 @@ pub fn detect_syntax(path: &Path) -> String {
 }
 
 pub fn highlight_content(content: &[String], extension: &str) -> Vec<Vec<Span<'static>>> {
-    let ps = SYNTAX_SET.get_or_init(|| SyntaxSet::load_defaults_newlines());
-    let ts = THEME_SET.get_or_init(|| ThemeSet::load_defaults());
+    let ps = SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines);
+    let ts = THEME_SET.get_or_init(ThemeSet::load_defaults);
 
     let syntax_def = ps
         .find_syntax_by_extension(extension)
@@ -112,7 +405,7 @@ pub fn highlight_content(content: &[String], extension: &str) -> Vec<Vec<Span<'s
 
     let mut result = Vec::with_capacity(content.len());
     for line in content {
-        let ranges = h.highlight_line(line, &ps).unwrap_or_default();
+        let ranges = h.highlight_line(line, ps).unwrap_or_default();
         let spans: Vec<Span<'static>> = ranges
             .into_iter()
             .map(|(style, text)| {
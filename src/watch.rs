@@ -0,0 +1,194 @@
+use crate::mounts::fs_type_of;
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last change before a panel reloads.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on the wait while changes keep coming (e.g. a running build).
+const MAX_DELAY: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Network and FUSE filesystems do not report changes made by other machines or the daemon behind them.
+const POLLED_FS_TYPES: [&str; 12] = ["nfs", "nfs4", "cifs", "smb3", "smbfs", "9p", "afs", "ceph", "glusterfs", "davfs", "virtiofs", "fuse"];
+
+/// How panels notice changes made outside fm84 (`watch=` in the settings file).
+#[derive(Clone, Copy, PartialEq)]
+pub enum WatchMode {
+    /// Native notifications, polling on network and FUSE filesystems.
+    Auto,
+    Native,
+    Poll,
+    Off,
+}
+
+impl WatchMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(WatchMode::Auto),
+            "native" => Some(WatchMode::Native),
+            "poll" => Some(WatchMode::Poll),
+            "off" => Some(WatchMode::Off),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WatchMode::Auto => "auto",
+            WatchMode::Native => "native",
+            WatchMode::Poll => "poll",
+            WatchMode::Off => "off",
+        }
+    }
+}
+
+/// Watches the directories shown in one panel and reports when it should reload. Watchers are created and
+/// directories registered on a thread of its own, as looking up the filesystem or polling a slow mount can block.
+pub struct PanelWatch {
+    mode: WatchMode,
+    dirs: Vec<PathBuf>,
+    /// Bumped with every new set of directories, so events still coming from the previous ones are ignored.
+    generation: u64,
+    commands: Option<Sender<(u64, Vec<PathBuf>)>>,
+    receiver: Receiver<(u64, notify::Result<Event>)>,
+    /// First and latest change of the burst not yet reloaded.
+    changed: Option<(Instant, Instant)>,
+}
+
+impl PanelWatch {
+    pub fn new(mode: WatchMode) -> Self {
+        let (sender, receiver) = channel();
+        let commands = (mode != WatchMode::Off).then(|| {
+            let (commands, command_receiver) = channel();
+            thread::spawn(move || run_watcher(mode, command_receiver, sender));
+            commands
+        });
+        Self { mode, dirs: Vec::new(), generation: 0, commands, receiver, changed: None }
+    }
+
+    /// Watches exactly `dirs` (the panel directory first) from now on.
+    pub fn watch(&mut self, dirs: Vec<PathBuf>) {
+        if dirs == self.dirs || self.mode == WatchMode::Off {
+            return;
+        }
+        self.generation += 1;
+        self.dirs = dirs.clone();
+        // Whatever piled up belongs to the directories just left
+        while self.receiver.try_recv().is_ok() {}
+        self.changed = None;
+        if let Some(commands) = &self.commands {
+            let _ = commands.send((self.generation, dirs));
+        }
+    }
+
+    /// True once a burst of changes has settled, or has kept going for `MAX_DELAY`.
+    pub fn poll(&mut self) -> bool {
+        while let Ok((generation, event)) = self.receiver.try_recv() {
+            // Reads (including fm84's own listing and previews) are not changes
+            if generation == self.generation && event.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_))) {
+                let now = Instant::now();
+                self.changed = Some((self.changed.map_or(now, |(first, _)| first), now));
+            }
+        }
+        match self.changed {
+            Some((first, last)) if last.elapsed() >= DEBOUNCE || first.elapsed() >= MAX_DELAY => {
+                self.changed = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The watcher of one panel, living on its own thread.
+struct WatchThread {
+    mode: WatchMode,
+    watcher: Option<Box<dyn Watcher + Send>>,
+    is_polling: bool,
+    dirs: Vec<PathBuf>,
+    /// Generation of the directories being watched, stamped on every event.
+    generation: Arc<AtomicU64>,
+    sender: Sender<(u64, notify::Result<Event>)>,
+}
+
+/// Applies the newest directory set whenever some arrive, until the panel's `PanelWatch` is dropped.
+fn run_watcher(mode: WatchMode, commands: Receiver<(u64, Vec<PathBuf>)>, sender: Sender<(u64, notify::Result<Event>)>) {
+    let mut watch = WatchThread { mode, watcher: None, is_polling: false, dirs: Vec::new(), generation: Arc::new(AtomicU64::new(0)), sender };
+    while let Ok(mut command) = commands.recv() {
+        // Directories passed through while the previous set was being registered are skipped
+        while let Ok(newer) = commands.try_recv() {
+            command = newer;
+        }
+        let (generation, dirs) = command;
+        // Stamped before registering, so no change in the new directories is lost; a late one from the old
+        // directories costs at most a spare reload
+        watch.generation.store(generation, Ordering::Relaxed);
+        watch.watch(dirs);
+    }
+}
+
+impl WatchThread {
+    /// Watches exactly `dirs`, reusing the watcher while the filesystem kind stays the same.
+    fn watch(&mut self, dirs: Vec<PathBuf>) {
+        let root_changed = dirs.first() != self.dirs.first();
+        let is_polling = match self.mode {
+            WatchMode::Poll => true,
+            WatchMode::Auto if root_changed => dirs.first().and_then(|dir| fs_type_of(dir)).is_some_and(|fs_type| is_polled(&fs_type)),
+            WatchMode::Native if root_changed => false,
+            _ => self.is_polling,
+        };
+        if is_polling != self.is_polling {
+            self.is_polling = is_polling;
+            self.watcher = None;
+        }
+
+        let old_dirs = std::mem::take(&mut self.dirs);
+        let kept: Vec<PathBuf> = match &mut self.watcher {
+            Some(watcher) => {
+                for dir in old_dirs.iter().filter(|dir| !dirs.contains(dir)) {
+                    let _ = watcher.unwatch(dir);
+                }
+                old_dirs.into_iter().filter(|dir| dirs.contains(dir)).collect()
+            }
+            None => Vec::new(),
+        };
+
+        let added: Vec<PathBuf> = dirs.iter().filter(|dir| !kept.contains(dir)).cloned().collect();
+        self.dirs = dirs;
+        if !self.add(&added) && !self.is_polling {
+            // Out of inotify watches or an unsupported filesystem: poll this panel instead
+            self.is_polling = true;
+            self.watcher = None;
+            let dirs = self.dirs.clone();
+            self.add(&dirs);
+        }
+    }
+
+    /// Starts watching `dirs`; false if any of them could not be watched.
+    fn add(&mut self, dirs: &[PathBuf]) -> bool {
+        if self.watcher.is_none() {
+            let (sender, generation) = (self.sender.clone(), Arc::clone(&self.generation));
+            let handler = move |event| {
+                let _ = sender.send((generation.load(Ordering::Relaxed), event));
+            };
+            let watcher: notify::Result<Box<dyn Watcher + Send>> = if self.is_polling {
+                PollWatcher::new(handler, Config::default().with_poll_interval(POLL_INTERVAL)).map(|watcher| Box::new(watcher) as _)
+            } else {
+                RecommendedWatcher::new(handler, Config::default()).map(|watcher| Box::new(watcher) as _)
+            };
+            self.watcher = watcher.ok();
+        }
+        let Some(watcher) = &mut self.watcher else { return false };
+        // Every directory gets its watch attempt, even after a failure
+        dirs.iter().filter(|dir| watcher.watch(dir, RecursiveMode::NonRecursive).is_err()).count() == 0
+    }
+}
+
+fn is_polled(fs_type: &str) -> bool {
+    POLLED_FS_TYPES.contains(&fs_type) || fs_type.starts_with("fuse.")
+}